@@ -0,0 +1,505 @@
+use crate::map::{self, OrderedHashMap};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FusedIterator;
+use std::ops::Index;
+
+/// One of the maps in a `ChainMap`, either owned by the chain or borrowed from the caller.
+pub enum Layer<'a, K, V, S = RandomState> {
+    /// A map owned by the chain, such as the one created by [`ChainMap::new_child`].
+    ///
+    /// [`ChainMap::new_child`]: struct.ChainMap.html#method.new_child
+    Owned(OrderedHashMap<K, V, S>),
+    /// A map borrowed from the caller. Writes through the chain land in it.
+    Borrowed(&'a mut OrderedHashMap<K, V, S>),
+}
+
+impl<K, V, S> Layer<'_, K, V, S> {
+    #[inline]
+    fn map(&self) -> &OrderedHashMap<K, V, S> {
+        match *self {
+            Layer::Owned(ref map) => map,
+            Layer::Borrowed(ref map) => map,
+        }
+    }
+
+    #[inline]
+    fn map_mut(&mut self) -> &mut OrderedHashMap<K, V, S> {
+        match *self {
+            Layer::Owned(ref mut map) => map,
+            Layer::Borrowed(ref mut map) => map,
+        }
+    }
+}
+
+impl<K, V, S> From<OrderedHashMap<K, V, S>> for Layer<'_, K, V, S> {
+    #[inline]
+    fn from(map: OrderedHashMap<K, V, S>) -> Self {
+        Layer::Owned(map)
+    }
+}
+
+impl<'a, K, V, S> From<&'a mut OrderedHashMap<K, V, S>> for Layer<'a, K, V, S> {
+    #[inline]
+    fn from(map: &'a mut OrderedHashMap<K, V, S>) -> Self {
+        Layer::Borrowed(map)
+    }
+}
+
+/// A stack of `OrderedHashMap`s searched front to back as a single map, like Python's
+/// `collections.ChainMap`.
+///
+/// Lookups return the value from the first map that has the key, while writes and removals
+/// only touch the first map, so the lower layers are never modified through the chain. The
+/// chain always has at least one map.
+///
+/// Iteration yields each distinct key once with the value a lookup would return. Keys are
+/// ordered as in Python: the keys of the last map in its order, followed by the keys first
+/// introduced by each earlier map, in that map's order.
+///
+/// # Examples
+///
+/// ```
+/// use ordered::{ChainMap, OrderedHashMap};
+///
+/// let mut defaults = OrderedHashMap::from([("color", "red"), ("user", "guest")]);
+/// let mut env = OrderedHashMap::from([("user", "admin")]);
+/// let mut cli = OrderedHashMap::new();
+///
+/// let mut config = ChainMap::from_layers(vec![(&mut cli).into(), (&mut env).into()]);
+/// config.push_parent(&mut defaults);
+/// config.insert("color", "blue");
+///
+/// assert_eq!(config[&"user"], "admin");
+/// assert_eq!(config.get_with_depth(&"user"), Some((1, &"admin")));
+/// let pairs: Vec<_> = config.iter().collect();
+/// assert_eq!(pairs, [(&"color", &"blue"), (&"user", &"admin")]);
+///
+/// drop(config);
+/// assert_eq!(cli[&"color"], "blue");
+/// ```
+pub struct ChainMap<'a, K, V, S = RandomState> {
+    layers: Vec<Layer<'a, K, V, S>>,
+}
+
+impl<K, V> ChainMap<'_, K, V, RandomState> {
+    /// Creates a chain with a single empty map.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a, K, V, S> ChainMap<'a, K, V, S> {
+    /// Creates a chain from maps given from the first searched to the last.
+    ///
+    /// If `layers` is empty, the chain starts with a single empty map.
+    pub fn from_layers<I>(layers: I) -> Self
+    where
+        I: IntoIterator<Item = Layer<'a, K, V, S>>,
+        S: Default,
+    {
+        let mut layers: Vec<_> = layers.into_iter().collect();
+        if layers.is_empty() {
+            layers.push(Layer::Owned(OrderedHashMap::default()));
+        }
+        ChainMap { layers }
+    }
+
+    /// Appends a map to the end of the chain, where it is searched last.
+    #[inline]
+    pub fn push_parent<L>(&mut self, parent: L)
+    where
+        L: Into<Layer<'a, K, V, S>>,
+    {
+        self.layers.push(parent.into());
+    }
+
+    /// Returns a new chain with an empty map in front of the current ones, like Python's
+    /// `ChainMap.new_child()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::ChainMap;
+    ///
+    /// let mut base = ChainMap::new();
+    /// base.insert("x", 1);
+    /// let mut child = base.new_child();
+    /// child.insert("x", 2);
+    /// assert_eq!(child[&"x"], 2);
+    /// assert_eq!(child.parents()[&"x"], 1);
+    /// ```
+    #[inline]
+    pub fn new_child(self) -> Self
+    where
+        S: Default,
+    {
+        self.new_child_with(OrderedHashMap::default())
+    }
+
+    /// Returns a new chain with `child` in front of the current maps.
+    pub fn new_child_with<L>(mut self, child: L) -> Self
+    where
+        L: Into<Layer<'a, K, V, S>>,
+    {
+        self.layers.insert(0, child.into());
+        self
+    }
+
+    /// Returns a chain of every map but the first, like Python's `ChainMap.parents`.
+    ///
+    /// If there is only one map, the returned chain holds a new empty map.
+    pub fn parents(&mut self) -> ChainMap<'_, K, V, S>
+    where
+        S: Default,
+    {
+        ChainMap::from_layers(
+            self.layers[1..]
+                .iter_mut()
+                .map(|layer| Layer::Borrowed(layer.map_mut())),
+        )
+    }
+
+    /// Returns the number of maps in the chain.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns the map at `depth`, where 0 is the first map searched.
+    #[inline]
+    pub fn map(&self, depth: usize) -> Option<&OrderedHashMap<K, V, S>> {
+        self.layers.get(depth).map(Layer::map)
+    }
+
+    /// Returns a mutable reference to the map at `depth`, where 0 is the first map searched.
+    #[inline]
+    pub fn map_mut(&mut self, depth: usize) -> Option<&mut OrderedHashMap<K, V, S>> {
+        self.layers.get_mut(depth).map(Layer::map_mut)
+    }
+
+    /// Returns the first map, which receives every write.
+    #[inline]
+    pub fn first(&self) -> &OrderedHashMap<K, V, S> {
+        self.layers[0].map()
+    }
+
+    /// Returns a mutable reference to the first map, which receives every write.
+    #[inline]
+    pub fn first_mut(&mut self) -> &mut OrderedHashMap<K, V, S> {
+        self.layers[0].map_mut()
+    }
+
+    /// Consumes the chain and returns its layers, from the first searched to the last.
+    #[inline]
+    pub fn into_layers(self) -> Vec<Layer<'a, K, V, S>> {
+        self.layers
+    }
+}
+
+impl<K, V, S> ChainMap<'_, K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns a reference to the value of the first map that contains the key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.get_with_depth(k).map(|(_, v)| v)
+    }
+
+    /// Returns the value of the first map that contains the key, along with the depth of that
+    /// map, which tells where the value came from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::{ChainMap, OrderedHashMap};
+    ///
+    /// let chain = ChainMap::from_layers(vec![
+    ///     OrderedHashMap::from([("a", 1)]).into(),
+    ///     OrderedHashMap::from([("a", 2), ("b", 3)]).into(),
+    /// ]);
+    /// assert_eq!(chain.get_with_depth(&"a"), Some((0, &1)));
+    /// assert_eq!(chain.get_with_depth(&"b"), Some((1, &3)));
+    /// assert_eq!(chain.get_with_depth(&"c"), None);
+    /// ```
+    pub fn get_with_depth<Q>(&self, k: &Q) -> Option<(usize, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.layers
+            .iter()
+            .enumerate()
+            .find_map(|(depth, layer)| layer.map().get(k).map(|v| (depth, v)))
+    }
+
+    /// Returns `true` if any map in the chain contains the key.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.layers.iter().any(|layer| layer.map().contains_key(k))
+    }
+
+    /// Inserts a key-value pair into the first map, shadowing the key in the other maps.
+    ///
+    /// Returns the old value of the key in the first map, if any.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.first_mut().insert(k, v)
+    }
+
+    /// Removes a key from the first map, returning its value there if it was present.
+    ///
+    /// The other maps are left untouched, so the key may still be visible through them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::{ChainMap, OrderedHashMap};
+    ///
+    /// let mut chain = ChainMap::from_layers(vec![
+    ///     OrderedHashMap::from([("a", 1)]).into(),
+    ///     OrderedHashMap::from([("a", 2)]).into(),
+    /// ]);
+    /// assert_eq!(chain.remove(&"a"), Some(1));
+    /// assert_eq!(chain.remove(&"a"), None);
+    /// assert_eq!(chain[&"a"], 2);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.first_mut().remove(k)
+    }
+
+    /// Clears the first map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.first_mut().clear();
+    }
+
+    /// Returns the number of distinct keys in the chain.
+    ///
+    /// This walks every map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns `true` if no map in the chain has any entry.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(|layer| layer.map().is_empty())
+    }
+
+    /// An iterator visiting each distinct key once, with the value a lookup would return.
+    ///
+    /// The keys of the last map come first, in its order, followed by the keys first
+    /// introduced by each earlier map, in that map's order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V, S> {
+        let depth = self.layers.len() - 1;
+        Iter {
+            chain: self,
+            depth,
+            keys: self.layers[depth].map().keys(),
+        }
+    }
+
+    /// An iterator visiting each distinct key once, in the order of [`iter`].
+    ///
+    /// [`iter`]: #method.iter
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// An iterator visiting the value of each distinct key, in the order of [`iter`].
+    ///
+    /// [`iter`]: #method.iter
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, S> Default for ChainMap<'_, K, V, S>
+where
+    S: Default,
+{
+    /// Creates a chain with a single empty map.
+    #[inline]
+    fn default() -> Self {
+        ChainMap::from_layers(None)
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for ChainMap<'_, K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: ?Sized + Eq + Hash,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value of the first map that contains the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no map contains the key.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S> fmt::Debug for ChainMap<'_, K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Formats the chain as `ChainMap({..}, {..})`, listing every map in search order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("ChainMap");
+        for layer in &self.layers {
+            tuple.field(layer.map());
+        }
+        tuple.finish()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a ChainMap<'_, K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, S>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V, S> {
+        self.iter()
+    }
+}
+
+/// An iterator over the distinct entries of a `ChainMap`.
+///
+/// This `struct` is created by the [`iter`] method on [`ChainMap`].
+///
+/// [`iter`]: struct.ChainMap.html#method.iter
+/// [`ChainMap`]: struct.ChainMap.html
+pub struct Iter<'a, K, V, S> {
+    chain: &'a ChainMap<'a, K, V, S>,
+    depth: usize,
+    keys: map::Keys<'a, K, V>,
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            match self.keys.next() {
+                Some(k) => {
+                    // Keys also present in a deeper map were already yielded with it.
+                    let shadowed = self.chain.layers[self.depth + 1..]
+                        .iter()
+                        .any(|layer| layer.map().contains_key(k));
+                    if !shadowed {
+                        return self.chain.get(k).map(|v| (k, v));
+                    }
+                }
+                None if self.depth == 0 => return None,
+                None => {
+                    self.depth -= 1;
+                    self.keys = self.chain.layers[self.depth].map().keys();
+                }
+            }
+        }
+    }
+}
+
+impl<K, V, S> FusedIterator for Iter<'_, K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChainMap;
+    use crate::OrderedHashMap;
+
+    #[test]
+    fn iteration_yields_each_key_once_in_python_order() {
+        let mut defaults = OrderedHashMap::from([("a", 1), ("b", 2), ("c", 3)]);
+        let mut env = OrderedHashMap::from([("d", 40), ("b", 20)]);
+        let chain = ChainMap::from_layers(vec![
+            OrderedHashMap::from([("c", 300), ("e", 500), ("d", 400)]).into(),
+            (&mut env).into(),
+            (&mut defaults).into(),
+        ]);
+
+        let pairs: Vec<_> = chain.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(
+            pairs,
+            vec![("a", 1), ("b", 20), ("c", 300), ("d", 400), ("e", 500)]
+        );
+        assert_eq!(chain.len(), 5);
+        assert_eq!(chain.keys().count(), 5);
+        assert_eq!(chain.values().sum::<i32>(), 1221);
+    }
+
+    #[test]
+    fn writes_go_to_the_first_map() {
+        let mut base = OrderedHashMap::from([("x", 1)]);
+        {
+            let mut chain = ChainMap::from_layers(vec![(&mut base).into()]).new_child();
+            assert_eq!(chain.depth(), 2);
+            chain.insert("x", 2);
+            chain.insert("y", 3);
+            assert_eq!(chain.get_with_depth(&"x"), Some((0, &2)));
+
+            {
+                let mut parents = chain.parents();
+                assert_eq!(parents.depth(), 1);
+                parents.insert("z", 4);
+                assert_eq!(parents.get(&"y"), None);
+            }
+            assert_eq!(chain.get_with_depth(&"z"), Some((1, &4)));
+            assert_eq!(chain.remove(&"x"), Some(2));
+            assert_eq!(chain.remove(&"z"), None);
+            assert_eq!(chain[&"x"], 1);
+            assert_eq!(
+                format!("{:?}", chain),
+                r#"ChainMap({"y": 3}, {"x": 1, "z": 4})"#
+            );
+            chain.clear();
+            assert!(!chain.is_empty());
+        }
+        assert_eq!(base, OrderedHashMap::from([("x", 1), ("z", 4)]));
+
+        let mut lone: ChainMap<&str, i32> = ChainMap::new();
+        assert!(lone.is_empty());
+        assert_eq!(lone.parents().depth(), 1);
+    }
+}
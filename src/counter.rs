@@ -0,0 +1,506 @@
+use crate::map::{self, OrderedHashMap};
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::{FromIterator, FusedIterator};
+use std::ops::{Add, BitAnd, BitOr, Deref, DerefMut, Index, Sub};
+
+/// A multiset that counts hashable items, like Python's `collections.Counter`.
+///
+/// The counts live in an `OrderedHashMap<T, isize>`, which is reachable through `Deref`, so
+/// items are reported in the order they were first counted and ties are broken the same way.
+/// Counts may be zero or negative; [`elements`] and the arithmetic operators only keep
+/// positive counts, as in Python.
+///
+/// [`elements`]: #method.elements
+///
+/// # Examples
+///
+/// ```
+/// use ordered::Counter;
+///
+/// let counter: Counter<char> = "abracadabra".chars().collect();
+/// assert_eq!(counter[&'a'], 5);
+/// assert_eq!(counter[&'z'], 0);
+/// assert_eq!(counter.most_common(3), [(&'a', 5), (&'b', 2), (&'r', 2)]);
+/// ```
+#[derive(Clone)]
+pub struct Counter<T, S = RandomState> {
+    map: OrderedHashMap<T, isize, S>,
+}
+
+impl<T> Counter<T, RandomState> {
+    /// Creates an empty `Counter`.
+    #[inline]
+    pub fn new() -> Counter<T, RandomState> {
+        Default::default()
+    }
+}
+
+impl<T, S> Counter<T, S> {
+    /// Creates an empty `Counter` which will use the given hash builder to hash items.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Counter<T, S> {
+        Counter {
+            map: OrderedHashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Returns the sum of all counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let counter: Counter<_> = "hello".chars().collect();
+    /// assert_eq!(counter.total(), 5);
+    /// ```
+    #[inline]
+    pub fn total(&self) -> isize {
+        self.map.values().sum()
+    }
+
+    /// Returns the `n` most common items and their counts, from the most common to the least.
+    ///
+    /// Items with equal counts are ordered by when they were first counted. Pass
+    /// [`len`] as `n` to list every item.
+    ///
+    /// [`len`]: ../map/struct.OrderedHashMap.html#method.len
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let counter: Counter<_> = "bbaac".chars().collect();
+    /// assert_eq!(counter.most_common(2), [(&'b', 2), (&'a', 2)]);
+    /// assert_eq!(counter.most_common(counter.len()), [(&'b', 2), (&'a', 2), (&'c', 1)]);
+    /// ```
+    pub fn most_common(&self, n: usize) -> Vec<(&T, isize)> {
+        let mut items: Vec<_> = self.map.iter().map(|(k, &v)| (k, v)).collect();
+        // `sort_by_key` is stable, which keeps ties in insertion order.
+        items.sort_by_key(|&(_, count)| Reverse(count));
+        items.truncate(n);
+        items
+    }
+
+    /// An iterator over each item repeated as many times as its count, in insertion order.
+    /// Items with a count of zero or less are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let mut counter: Counter<_> = "abab".chars().collect();
+    /// counter.subtract("bbbc".chars());
+    /// let elements: String = counter.elements().collect();
+    /// assert_eq!(elements, "aa");
+    /// ```
+    #[inline]
+    pub fn elements(&self) -> Elements<'_, T> {
+        Elements {
+            iter: self.map.iter(),
+            current: None,
+        }
+    }
+
+    /// Unwraps the underlying map of counts.
+    #[inline]
+    pub fn into_map(self) -> OrderedHashMap<T, isize, S> {
+        self.map
+    }
+}
+
+impl<T, S> Counter<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Adds one to the count of each item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let mut counter = Counter::new();
+    /// counter.update(vec!["x", "y"]);
+    /// counter.update(vec!["y"]);
+    /// assert_eq!(counter[&"y"], 2);
+    /// ```
+    pub fn update<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            *self.map.entry(item).or_insert(0) += 1;
+        }
+    }
+
+    /// Subtracts one from the count of each item. Counts may become zero or negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let mut counter: Counter<_> = vec!["x"].into_iter().collect();
+    /// counter.subtract(vec!["x", "x", "y"]);
+    /// assert_eq!(counter[&"x"], -1);
+    /// assert_eq!(counter[&"y"], -1);
+    /// ```
+    pub fn subtract<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            *self.map.entry(item).or_insert(0) -= 1;
+        }
+    }
+
+    /// Returns the count of `item`, which is zero if it was never counted.
+    #[inline]
+    pub fn count<Q>(&self, item: &Q) -> isize
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(item).cloned().unwrap_or(0)
+    }
+
+    /// Combines two counters item by item, keeping the positive results. The items of `self`
+    /// come first, followed by the items only in `other`.
+    fn combine<F>(&self, other: &Counter<T, S>, f: F) -> Counter<T, S>
+    where
+        T: Clone,
+        S: Default,
+        F: Fn(isize, isize) -> isize,
+    {
+        let mut result = Counter::with_hasher(S::default());
+        for (item, &count) in self.map.iter() {
+            let count = f(count, other.count(item));
+            if count > 0 {
+                result.map.insert(item.clone(), count);
+            }
+        }
+        for (item, &count) in other.map.iter() {
+            if !self.map.contains_key(item) {
+                let count = f(0, count);
+                if count > 0 {
+                    result.map.insert(item.clone(), count);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<T, S> Default for Counter<T, S>
+where
+    S: Default,
+{
+    /// Creates an empty `Counter<T, S>`, with the `Default` value for the hasher.
+    #[inline]
+    fn default() -> Counter<T, S> {
+        Counter {
+            map: OrderedHashMap::default(),
+        }
+    }
+}
+
+impl<T, S> Deref for Counter<T, S> {
+    type Target = OrderedHashMap<T, isize, S>;
+
+    #[inline]
+    fn deref(&self) -> &OrderedHashMap<T, isize, S> {
+        &self.map
+    }
+}
+
+impl<T, S> DerefMut for Counter<T, S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut OrderedHashMap<T, isize, S> {
+        &mut self.map
+    }
+}
+
+impl<T, Q, S> Index<&Q> for Counter<T, S>
+where
+    T: Eq + Hash + Borrow<Q>,
+    Q: ?Sized + Eq + Hash,
+    S: BuildHasher,
+{
+    type Output = isize;
+
+    /// Returns the count of the supplied item, which is zero if it was never counted.
+    #[inline]
+    fn index(&self, item: &Q) -> &isize {
+        self.map.get(item).unwrap_or(&0)
+    }
+}
+
+impl<T, S1, S2> PartialEq<Counter<T, S2>> for Counter<T, S1>
+where
+    T: Eq + Hash,
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    /// Compares the counts as multisets, ignoring the order, like Python's `Counter`.
+    #[inline]
+    fn eq(&self, other: &Counter<T, S2>) -> bool {
+        self.map.eq_unordered(&other.map)
+    }
+}
+
+impl<T, S> Eq for Counter<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> fmt::Debug for Counter<T, S>
+where
+    T: fmt::Debug,
+{
+    /// Formats the counter as `Counter({k: v, ...})` in insertion order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Counter").field(&self.map).finish()
+    }
+}
+
+impl<T, S> FromIterator<T> for Counter<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Counter<T, S> {
+        let mut counter = Counter::default();
+        counter.update(iter);
+        counter
+    }
+}
+
+impl<T, S> Extend<T> for Counter<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Adds one to the count of each item, the same as [`update`].
+    ///
+    /// [`update`]: #method.update
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a Counter<T, S> {
+    type Item = (&'a T, &'a isize);
+    type IntoIter = map::Iter<'a, T, isize>;
+
+    #[inline]
+    fn into_iter(self) -> map::Iter<'a, T, isize> {
+        self.map.iter()
+    }
+}
+
+impl<T, S> IntoIterator for Counter<T, S> {
+    type Item = (T, isize);
+    type IntoIter = map::IntoIter<T, isize>;
+
+    #[inline]
+    fn into_iter(self) -> map::IntoIter<T, isize> {
+        self.map.into_iter()
+    }
+}
+
+impl<T, S> Add<&Counter<T, S>> for &Counter<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Counter<T, S>;
+
+    /// Adds the counts of both counters, keeping only positive results.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let a: Counter<_> = "aab".chars().collect();
+    /// let mut b: Counter<_> = "bc".chars().collect();
+    /// b.subtract("bbb".chars());
+    /// assert_eq!(format!("{:?}", &a + &b), "Counter({'a': 2, 'c': 1})");
+    /// ```
+    fn add(self, other: &Counter<T, S>) -> Counter<T, S> {
+        self.combine(other, |a, b| a + b)
+    }
+}
+
+impl<T, S> Sub<&Counter<T, S>> for &Counter<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Counter<T, S>;
+
+    /// Subtracts the counts of `other`, keeping only positive results.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let a: Counter<_> = "aaab".chars().collect();
+    /// let b: Counter<_> = "abbc".chars().collect();
+    /// assert_eq!(format!("{:?}", &a - &b), "Counter({'a': 2})");
+    /// ```
+    fn sub(self, other: &Counter<T, S>) -> Counter<T, S> {
+        self.combine(other, |a, b| a - b)
+    }
+}
+
+impl<T, S> BitAnd<&Counter<T, S>> for &Counter<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Counter<T, S>;
+
+    /// Keeps the minimum of the counts of both counters, and only positive results.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let a: Counter<_> = "aaab".chars().collect();
+    /// let b: Counter<_> = "abbc".chars().collect();
+    /// assert_eq!(format!("{:?}", &a & &b), "Counter({'a': 1, 'b': 1})");
+    /// ```
+    fn bitand(self, other: &Counter<T, S>) -> Counter<T, S> {
+        self.combine(other, isize::min)
+    }
+}
+
+impl<T, S> BitOr<&Counter<T, S>> for &Counter<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Counter<T, S>;
+
+    /// Keeps the maximum of the counts of both counters, and only positive results.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Counter;
+    ///
+    /// let a: Counter<_> = "aaab".chars().collect();
+    /// let b: Counter<_> = "abbc".chars().collect();
+    /// assert_eq!(format!("{:?}", &a | &b), "Counter({'a': 3, 'b': 2, 'c': 1})");
+    /// ```
+    fn bitor(self, other: &Counter<T, S>) -> Counter<T, S> {
+        self.combine(other, isize::max)
+    }
+}
+
+/// An iterator over the items of a `Counter`, each repeated as many times as its count.
+///
+/// This `struct` is created by the [`elements`] method on [`Counter`].
+///
+/// [`elements`]: struct.Counter.html#method.elements
+/// [`Counter`]: struct.Counter.html
+pub struct Elements<'a, T> {
+    iter: map::Iter<'a, T, isize>,
+    current: Option<(&'a T, isize)>,
+}
+
+impl<T> Clone for Elements<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Elements {
+            iter: self.iter.clone(),
+            current: self.current,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Elements<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some((item, ref mut remaining)) = self.current {
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    return Some(item);
+                }
+            }
+            let (item, &count) = self.iter.next()?;
+            self.current = Some((item, count));
+        }
+    }
+}
+
+impl<T> FusedIterator for Elements<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::Counter;
+
+    #[test]
+    fn counts_and_ties() {
+        let mut counter: Counter<&str> = "b a c a b d".split(' ').collect();
+        counter.update(vec!["d", "e"]);
+        counter.subtract(vec!["e", "e", "f"]);
+
+        assert_eq!(counter.total(), 5);
+        assert_eq!(counter.count("e"), -1);
+        assert_eq!(counter.count("z"), 0);
+        assert_eq!(
+            counter.most_common(counter.len()),
+            vec![
+                (&"b", 2),
+                (&"a", 2),
+                (&"d", 2),
+                (&"c", 1),
+                (&"e", -1),
+                (&"f", -1)
+            ]
+        );
+        assert_eq!(counter.most_common(1), vec![(&"b", 2)]);
+        let elements: Vec<_> = counter.elements().cloned().collect();
+        assert_eq!(elements, vec!["b", "b", "a", "a", "c", "d", "d"]);
+
+        *counter.entry("z").or_insert(0) += 3;
+        assert_eq!(counter.most_common(1), vec![(&"z", 3)]);
+    }
+
+    #[test]
+    fn arithmetic_drops_non_positive_counts() {
+        let mut a: Counter<char> = "aaabbc".chars().collect();
+        a.subtract("dd".chars());
+        let b: Counter<char> = "abbbe".chars().collect();
+
+        let sum = &a + &b;
+        assert_eq!(
+            format!("{:?}", sum),
+            "Counter({'a': 4, 'b': 5, 'c': 1, 'e': 1})"
+        );
+        let diff = &b - &a;
+        assert_eq!(format!("{:?}", diff), "Counter({'b': 1, 'e': 1, 'd': 2})");
+        let min = &a & &b;
+        assert_eq!(format!("{:?}", min), "Counter({'a': 1, 'b': 2})");
+        let max = &a | &b;
+        assert_eq!(
+            format!("{:?}", max),
+            "Counter({'a': 3, 'b': 3, 'c': 1, 'e': 1})"
+        );
+
+        let reordered: Counter<char> = "ebbba".chars().collect();
+        assert_eq!(reordered, b);
+        assert!(reordered != a);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::map::{self, OrderedHashMap};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::ops::{Deref, DerefMut, Index, IndexMut};
+
+/// An `OrderedHashMap` that fills in missing keys from a factory, like Python's
+/// `collections.defaultdict`.
+///
+/// The factory only runs when a key is accessed mutably through [`get_or_default_mut`] or
+/// `map[key]` in a mutable context. Read-only lookups such as [`get`] never insert anything,
+/// and every other method of `OrderedHashMap` is available through `Deref`.
+///
+/// [`get_or_default_mut`]: #method.get_or_default_mut
+/// [`get`]: ../map/struct.OrderedHashMap.html#method.get
+///
+/// # Examples
+///
+/// ```
+/// use ordered::DefaultOrderedHashMap;
+///
+/// let mut groups = DefaultOrderedHashMap::new(Vec::new);
+/// for word in ["apple", "bob", "avocado", "banana", "cherry"].iter() {
+///     groups[word.as_bytes()[0]].push(*word);
+/// }
+///
+/// assert_eq!(groups[b'a'], ["apple", "avocado"]);
+/// assert_eq!(groups.get(&b'z'), None);
+/// let firsts: Vec<_> = groups.keys().map(|&b| b as char).collect();
+/// assert_eq!(firsts, ['a', 'b', 'c']);
+/// ```
+#[derive(Clone)]
+pub struct DefaultOrderedHashMap<K, V, F, S = RandomState> {
+    map: OrderedHashMap<K, V, S>,
+    default: F,
+}
+
+impl<K, V, F> DefaultOrderedHashMap<K, V, F, RandomState>
+where
+    F: Fn() -> V,
+{
+    /// Creates an empty `DefaultOrderedHashMap` that uses `default` to create missing values.
+    #[inline]
+    pub fn new(default: F) -> DefaultOrderedHashMap<K, V, F, RandomState> {
+        DefaultOrderedHashMap::from_map(OrderedHashMap::new(), default)
+    }
+}
+
+impl<K, V, F, S> DefaultOrderedHashMap<K, V, F, S>
+where
+    F: Fn() -> V,
+{
+    /// Creates an empty `DefaultOrderedHashMap` which will use the given hash builder to hash
+    /// keys and `default` to create missing values.
+    #[inline]
+    pub fn with_hasher(default: F, hash_builder: S) -> DefaultOrderedHashMap<K, V, F, S> {
+        DefaultOrderedHashMap::from_map(OrderedHashMap::with_hasher(hash_builder), default)
+    }
+
+    /// Wraps an existing map, keeping its entries and their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::{DefaultOrderedHashMap, OrderedHashMap};
+    ///
+    /// let mut counts = DefaultOrderedHashMap::from_map(OrderedHashMap::from([("b", 1)]), || 0);
+    /// counts["a"] += 1;
+    /// counts["b"] += 1;
+    /// assert_eq!(counts.into_map(), OrderedHashMap::from([("b", 2), ("a", 1)]));
+    /// ```
+    #[inline]
+    pub fn from_map(map: OrderedHashMap<K, V, S>, default: F) -> DefaultOrderedHashMap<K, V, F, S> {
+        DefaultOrderedHashMap { map, default }
+    }
+
+    /// Returns the factory used to create missing values.
+    #[inline]
+    pub fn default_factory(&self) -> &F {
+        &self.default
+    }
+
+    /// Unwraps the underlying map.
+    #[inline]
+    pub fn into_map(self) -> OrderedHashMap<K, V, S> {
+        self.map
+    }
+}
+
+impl<K, V, F, S> DefaultOrderedHashMap<K, V, F, S>
+where
+    K: Eq + Hash,
+    F: Fn() -> V,
+    S: BuildHasher,
+{
+    /// Returns a mutable reference to the value of `key`, first inserting a value from the
+    /// factory at the end of the order if the key is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::DefaultOrderedHashMap;
+    ///
+    /// let mut map = DefaultOrderedHashMap::new(String::new);
+    /// map.get_or_default_mut(1).push_str("one");
+    /// map.get_or_default_mut(1).push_str("!");
+    /// assert_eq!(map[1], "one!");
+    /// ```
+    #[inline]
+    pub fn get_or_default_mut(&mut self, key: K) -> &mut V {
+        let default = &self.default;
+        self.map.entry(key).or_insert_with(default)
+    }
+}
+
+impl<K, V, F, S> Deref for DefaultOrderedHashMap<K, V, F, S> {
+    type Target = OrderedHashMap<K, V, S>;
+
+    #[inline]
+    fn deref(&self) -> &OrderedHashMap<K, V, S> {
+        &self.map
+    }
+}
+
+impl<K, V, F, S> DerefMut for DefaultOrderedHashMap<K, V, F, S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut OrderedHashMap<K, V, S> {
+        &mut self.map
+    }
+}
+
+impl<K, V, F, S> Index<K> for DefaultOrderedHashMap<K, V, F, S>
+where
+    K: Eq + Hash,
+    F: Fn() -> V,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// A shared reference cannot insert, so this does not run the factory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the map.
+    #[inline]
+    fn index(&self, key: K) -> &V {
+        self.map.get(&key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, F, S> IndexMut<K> for DefaultOrderedHashMap<K, V, F, S>
+where
+    K: Eq + Hash,
+    F: Fn() -> V,
+    S: BuildHasher,
+{
+    /// Returns a mutable reference to the value corresponding to the supplied key, inserting
+    /// a value from the factory if the key is missing.
+    #[inline]
+    fn index_mut(&mut self, key: K) -> &mut V {
+        self.get_or_default_mut(key)
+    }
+}
+
+impl<K, V, F, S> fmt::Debug for DefaultOrderedHashMap<K, V, F, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Formats the map as `{k: v, ...}` in insertion order, leaving out the factory.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<'a, K, V, F, S> IntoIterator for &'a DefaultOrderedHashMap<K, V, F, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = map::Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> map::Iter<'a, K, V> {
+        self.map.iter()
+    }
+}
+
+impl<'a, K, V, F, S> IntoIterator for &'a mut DefaultOrderedHashMap<K, V, F, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = map::IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> map::IterMut<'a, K, V> {
+        self.map.iter_mut()
+    }
+}
+
+impl<K, V, F, S> IntoIterator for DefaultOrderedHashMap<K, V, F, S> {
+    type Item = (K, V);
+    type IntoIter = map::IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> map::IntoIter<K, V> {
+        self.map.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultOrderedHashMap;
+    use std::cell::Cell;
+
+    #[test]
+    fn factory_runs_only_on_mutable_access() {
+        let calls = Cell::new(0);
+        let mut map = DefaultOrderedHashMap::new(|| {
+            calls.set(calls.get() + 1);
+            Vec::new()
+        });
+
+        assert_eq!(map.get(&"a"), None);
+        assert!(!map.contains_key(&"a"));
+        assert_eq!(map.get_index_of(&"a"), None);
+        assert_eq!(calls.get(), 0);
+
+        map["b"].push(1);
+        map["a"].push(2);
+        map["b"].push(3);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(map["b"], vec![1, 3]);
+        assert_eq!(calls.get(), 2);
+
+        map.insert("c", vec![4]);
+        map.get_or_default_mut("c").push(5);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(
+            format!("{:?}", map),
+            r#"{"b": [1, 3], "a": [2], "c": [4, 5]}"#
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn shared_index_of_missing_key_panics() {
+        let map: DefaultOrderedHashMap<i32, i32, _> = DefaultOrderedHashMap::new(|| 0);
+        let _ = map[1];
+    }
+}
@@ -0,0 +1,516 @@
+use std::collections::vec_deque::{self, VecDeque};
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Index, IndexMut};
+
+/// A double-ended queue with the semantics of Python's `collections.deque`.
+///
+/// On top of `VecDeque` it adds an optional `maxlen`: a bounded deque never grows past it,
+/// and adding to one end discards items from the opposite end, which makes it a sliding
+/// window. It also rotates by signed step counts and searches by value like its Python
+/// counterpart.
+///
+/// # Examples
+///
+/// ```
+/// use ordered::Deque;
+///
+/// let mut window = Deque::with_maxlen(3);
+/// for x in 1..=5 {
+///     window.append(x);
+/// }
+/// assert_eq!(window, Deque::from(vec![3, 4, 5]));
+///
+/// window.rotate(-1);
+/// assert_eq!(window, Deque::from(vec![4, 5, 3]));
+/// ```
+#[derive(Clone)]
+pub struct Deque<T> {
+    items: VecDeque<T>,
+    maxlen: Option<usize>,
+}
+
+impl<T> Deque<T> {
+    /// Creates an empty, unbounded `Deque`.
+    #[inline]
+    pub fn new() -> Deque<T> {
+        Deque {
+            items: VecDeque::new(),
+            maxlen: None,
+        }
+    }
+
+    /// Creates an empty `Deque` bounded to `maxlen` items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let mut deque = Deque::with_maxlen(2);
+    /// deque.append(1);
+    /// deque.append(2);
+    /// assert_eq!(deque.append(3), Some(1));
+    /// assert_eq!(deque.maxlen(), Some(2));
+    /// ```
+    #[inline]
+    pub fn with_maxlen(maxlen: usize) -> Deque<T> {
+        Deque {
+            items: VecDeque::new(),
+            maxlen: Some(maxlen),
+        }
+    }
+
+    /// Returns the maximum length of a bounded deque, or `None` if it is unbounded.
+    #[inline]
+    pub fn maxlen(&self) -> Option<usize> {
+        self.maxlen
+    }
+
+    /// Returns the number of items in the deque.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the deque is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Removes all items from the deque.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Returns `true` if the deque is bounded and holds `maxlen` items.
+    #[inline]
+    fn is_full(&self) -> bool {
+        matches!(self.maxlen, Some(maxlen) if self.items.len() >= maxlen)
+    }
+
+    /// Adds `value` to the right end of the deque.
+    ///
+    /// If the deque is bounded and full, the leftmost item is discarded and returned. With a
+    /// `maxlen` of 0 the value itself is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let mut deque = Deque::new();
+    /// deque.append(1);
+    /// deque.append(2);
+    /// assert_eq!(deque.back(), Some(&2));
+    /// ```
+    pub fn append(&mut self, value: T) -> Option<T> {
+        if self.maxlen == Some(0) {
+            return Some(value);
+        }
+        let discarded = if self.is_full() {
+            self.items.pop_front()
+        } else {
+            None
+        };
+        self.items.push_back(value);
+        discarded
+    }
+
+    /// Adds `value` to the left end of the deque.
+    ///
+    /// If the deque is bounded and full, the rightmost item is discarded and returned. With a
+    /// `maxlen` of 0 the value itself is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let mut deque = Deque::with_maxlen(2);
+    /// deque.appendleft(1);
+    /// deque.appendleft(2);
+    /// assert_eq!(deque.appendleft(3), Some(1));
+    /// assert_eq!(deque, Deque::from(vec![3, 2]));
+    /// ```
+    pub fn appendleft(&mut self, value: T) -> Option<T> {
+        if self.maxlen == Some(0) {
+            return Some(value);
+        }
+        let discarded = if self.is_full() {
+            self.items.pop_back()
+        } else {
+            None
+        };
+        self.items.push_front(value);
+        discarded
+    }
+
+    /// Removes and returns the rightmost item.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop_back()
+    }
+
+    /// Removes and returns the leftmost item.
+    #[inline]
+    pub fn popleft(&mut self) -> Option<T> {
+        self.items.pop_front()
+    }
+
+    /// Adds every item of `iter` to the left end, one at a time, so they end up in reverse
+    /// order, like Python's `deque.extendleft`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let mut deque = Deque::from(vec![4]);
+    /// deque.extendleft(vec![3, 2, 1]);
+    /// assert_eq!(deque, Deque::from(vec![1, 2, 3, 4]));
+    /// ```
+    pub fn extendleft<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.appendleft(value);
+        }
+    }
+
+    /// Rotates the deque `n` steps to the right, or `-n` steps to the left if `n` is negative.
+    ///
+    /// Rotating one step to the right moves the rightmost item to the left end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let mut deque: Deque<_> = (1..=5).collect();
+    /// deque.rotate(2);
+    /// assert_eq!(deque, Deque::from(vec![4, 5, 1, 2, 3]));
+    /// deque.rotate(-3);
+    /// assert_eq!(deque, Deque::from(vec![2, 3, 4, 5, 1]));
+    /// ```
+    pub fn rotate(&mut self, n: isize) {
+        let len = self.items.len();
+        if len == 0 {
+            return;
+        }
+        let steps = n.unsigned_abs() % len;
+        if n >= 0 {
+            self.items.rotate_right(steps);
+        } else {
+            self.items.rotate_left(steps);
+        }
+    }
+
+    /// Reverses the items of the deque in place.
+    #[inline]
+    pub fn reverse(&mut self) {
+        self.items.make_contiguous().reverse();
+    }
+
+    /// Returns the leftmost item.
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    /// Returns the rightmost item.
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// Returns the item at `index`, counting from the left.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    /// Returns a mutable reference to the item at `index`, counting from the left.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.items.get_mut(index)
+    }
+
+    /// Inserts `value` at `index`, shifting the items after it to the right.
+    ///
+    /// A bounded deque that is full refuses the insertion and hands the value back, like
+    /// Python raising `IndexError`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let mut deque = Deque::with_maxlen(3);
+    /// deque.extend(vec![1, 3]);
+    /// assert_eq!(deque.insert(1, 2), Ok(()));
+    /// assert_eq!(deque.insert(0, 0), Err(0));
+    /// assert_eq!(deque, Deque::from(vec![1, 2, 3]));
+    /// ```
+    pub fn insert(&mut self, index: usize, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.items.insert(index, value);
+        Ok(())
+    }
+
+    /// An iterator over the items from left to right.
+    #[inline]
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// A mutable iterator over the items from left to right.
+    #[inline]
+    pub fn iter_mut(&mut self) -> vec_deque::IterMut<'_, T> {
+        self.items.iter_mut()
+    }
+
+    /// Returns the underlying `VecDeque`, dropping the bound.
+    #[inline]
+    pub fn into_vec_deque(self) -> VecDeque<T> {
+        self.items
+    }
+}
+
+impl<T: PartialEq> Deque<T> {
+    /// Returns the number of items equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let deque = Deque::from(vec![1, 2, 1, 3, 1]);
+    /// assert_eq!(deque.count(&1), 3);
+    /// assert_eq!(deque.count(&4), 0);
+    /// ```
+    #[inline]
+    pub fn count(&self, value: &T) -> usize {
+        self.items.iter().filter(|item| *item == value).count()
+    }
+
+    /// Removes and returns the leftmost item equal to `value`, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let mut deque = Deque::from(vec![1, 2, 1]);
+    /// assert_eq!(deque.remove(&1), Some(1));
+    /// assert_eq!(deque.remove(&4), None);
+    /// assert_eq!(deque, Deque::from(vec![2, 1]));
+    /// ```
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let index = self.items.iter().position(|item| item == value)?;
+        self.items.remove(index)
+    }
+
+    /// Returns the index of the leftmost item equal to `value` among the positions
+    /// `start..stop`, like Python's `deque.index(value, start, stop)`.
+    ///
+    /// `stop` is clamped to the length of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::Deque;
+    ///
+    /// let deque = Deque::from(vec!['a', 'b', 'a', 'c']);
+    /// assert_eq!(deque.index(&'a', 0, usize::MAX), Some(0));
+    /// assert_eq!(deque.index(&'a', 1, 4), Some(2));
+    /// assert_eq!(deque.index(&'a', 1, 2), None);
+    /// ```
+    pub fn index(&self, value: &T, start: usize, stop: usize) -> Option<usize> {
+        let stop = usize::min(stop, self.items.len());
+        if start >= stop {
+            return None;
+        }
+        self.items
+            .range(start..stop)
+            .position(|item| item == value)
+            .map(|i| start + i)
+    }
+}
+
+impl<T> Default for Deque<T> {
+    /// Creates an empty, unbounded `Deque<T>`.
+    #[inline]
+    fn default() -> Deque<T> {
+        Deque::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Deque<T> {
+    /// Compares the items in order, ignoring `maxlen`, like Python.
+    #[inline]
+    fn eq(&self, other: &Deque<T>) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<T: Eq> Eq for Deque<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    /// Formats the deque as `[a, b, ...]` from left to right.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.items.iter()).finish()
+    }
+}
+
+impl<T> Index<usize> for Deque<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        &self.items[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Deque<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.items[index]
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    /// Creates an unbounded deque from the items in order.
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Deque<T> {
+        Deque {
+            items: iter.into_iter().collect(),
+            maxlen: None,
+        }
+    }
+}
+
+impl<T> Extend<T> for Deque<T> {
+    /// Adds every item of `iter` to the right end, discarding from the left end if the deque
+    /// is bounded.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.append(value);
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Deque<T> {
+    /// Creates an unbounded deque from the items of `vec`.
+    #[inline]
+    fn from(vec: Vec<T>) -> Deque<T> {
+        Deque {
+            items: VecDeque::from(vec),
+            maxlen: None,
+        }
+    }
+}
+
+impl<T> From<VecDeque<T>> for Deque<T> {
+    /// Creates an unbounded deque from the items of `items`.
+    #[inline]
+    fn from(items: VecDeque<T>) -> Deque<T> {
+        Deque {
+            items,
+            maxlen: None,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Deque<T> {
+    type Item = &'a T;
+    type IntoIter = vec_deque::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> vec_deque::Iter<'a, T> {
+        self.items.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Deque<T> {
+    type Item = &'a mut T;
+    type IntoIter = vec_deque::IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> vec_deque::IterMut<'a, T> {
+        self.items.iter_mut()
+    }
+}
+
+impl<T> IntoIterator for Deque<T> {
+    type Item = T;
+    type IntoIter = vec_deque::IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> vec_deque::IntoIter<T> {
+        self.items.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deque;
+
+    #[test]
+    fn bounded_deque_discards_from_the_opposite_end() {
+        let mut deque = Deque::with_maxlen(3);
+        deque.extend(1..=4);
+        assert_eq!(deque, Deque::from(vec![2, 3, 4]));
+        deque.extendleft(vec![1, 0]);
+        assert_eq!(deque, Deque::from(vec![0, 1, 2]));
+        assert_eq!(deque.append(3), Some(0));
+        assert_eq!(deque.pop(), Some(3));
+        assert_eq!(deque.append(3), None);
+        assert_eq!(deque.popleft(), Some(1));
+        assert_eq!(deque.len(), 2);
+
+        let mut empty = Deque::with_maxlen(0);
+        assert_eq!(empty.append(1), Some(1));
+        assert_eq!(empty.appendleft(2), Some(2));
+        assert!(empty.is_empty());
+
+        let mut huge = Deque::with_maxlen(usize::MAX);
+        assert_eq!(huge.append(1), None);
+        assert_eq!(huge.maxlen(), Some(usize::MAX));
+    }
+
+    #[test]
+    fn rotate_with_any_count() {
+        let mut deque: Deque<_> = (0..5).collect();
+        deque.rotate(7);
+        assert_eq!(deque, Deque::from(vec![3, 4, 0, 1, 2]));
+        deque.rotate(-12);
+        assert_eq!(deque, Deque::from(vec![0, 1, 2, 3, 4]));
+        deque.rotate(isize::MIN);
+        assert_eq!(deque.len(), 5);
+
+        let mut empty: Deque<i32> = Deque::new();
+        empty.rotate(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn search_and_remove_by_value() {
+        let mut deque = Deque::from(vec![1, 2, 3, 2, 1]);
+        assert_eq!(deque.count(&2), 2);
+        assert_eq!(deque.index(&2, 2, 5), Some(3));
+        assert_eq!(deque.index(&2, 4, 2), None);
+        assert_eq!(deque.remove(&2), Some(2));
+        assert_eq!(deque, Deque::from(vec![1, 3, 2, 1]));
+        deque.reverse();
+        assert_eq!(format!("{:?}", deque), "[1, 2, 3, 1]");
+        deque[0] = 5;
+        assert_eq!(deque.front(), Some(&5));
+    }
+}
@@ -0,0 +1,460 @@
+use crate::lru::Displaced;
+use crate::map::{self, OrderedHashMap};
+use crate::set::OrderedHashSet;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter;
+use std::mem;
+
+/// A least-frequently-used cache with O(1) `get`, `put` and eviction.
+///
+/// Every key sits in the bucket of its access count. Buckets are `OrderedHashSet`s kept in
+/// an `OrderedHashMap` keyed by frequency and linked from the lowest frequency to the
+/// highest, so moving a key to the next bucket, evicting from the lowest one and finding the
+/// next lowest frequency are all amortized O(1). Within a bucket keys are ordered by when
+/// they reached that frequency, so ties are evicted oldest first.
+///
+/// Keys are stored both in the entry map and in their bucket, so they must be `Clone`.
+///
+/// # Examples
+///
+/// ```
+/// use ordered::{Displaced, LfuCache};
+///
+/// let mut cache = LfuCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// cache.get(&"a");
+///
+/// // "b" has been used the least.
+/// assert_eq!(cache.put("c", 3), Some(Displaced::Evicted("b", 2)));
+/// assert_eq!(cache.frequency(&"a"), Some(2));
+/// assert_eq!(cache.frequency(&"c"), Some(1));
+/// ```
+#[derive(Clone)]
+pub struct LfuCache<K, V, S = RandomState> {
+    entries: OrderedHashMap<K, (usize, V), S>,
+    buckets: OrderedHashMap<usize, Bucket<K, S>, S>,
+    hash_builder: S,
+    capacity: usize,
+    min_freq: usize,
+}
+
+/// The keys used `freq` times, linked to the buckets of the nearest lower and higher
+/// frequencies. A link of 0 means there is no such bucket, as frequencies start at 1.
+#[derive(Clone)]
+struct Bucket<K, S> {
+    keys: OrderedHashSet<K, S>,
+    prev: usize,
+    next: usize,
+}
+
+impl<K, V> LfuCache<K, V, RandomState> {
+    /// Creates an empty `LfuCache` that holds at most `capacity` entries.
+    #[inline]
+    pub fn new(capacity: usize) -> LfuCache<K, V, RandomState> {
+        LfuCache::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S: Clone> LfuCache<K, V, S> {
+    /// Creates an empty `LfuCache` that holds at most `capacity` entries and uses the given
+    /// hash builder to hash keys.
+    #[inline]
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> LfuCache<K, V, S> {
+        LfuCache {
+            entries: OrderedHashMap::with_hasher(hash_builder.clone()).unranked(),
+            buckets: OrderedHashMap::with_hasher(hash_builder.clone()).unranked(),
+            hash_builder,
+            capacity,
+            min_freq: 0,
+        }
+    }
+}
+
+impl<K, V, S> LfuCache<K, V, S> {
+    /// Returns the maximum number of entries the cache holds.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the cache contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all entries from the cache.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.buckets.clear();
+        self.min_freq = 0;
+    }
+
+    /// An iterator over the entries in the order their keys were first inserted, without
+    /// counting as a use.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, (_, v))| (k, v))
+    }
+}
+
+impl<K, V, S> LfuCache<K, V, S>
+where
+    K: Eq + Hash + Clone,
+    S: BuildHasher + Clone,
+{
+    /// Inserts a key-value pair and counts it as a use of the key.
+    ///
+    /// If the key was already cached, its value is replaced and the old value is returned as
+    /// [`Displaced::Replaced`]. Otherwise, if the cache was full, the least frequently used
+    /// entry is evicted and returned as [`Displaced::Evicted`], the oldest one among ties. A
+    /// new key starts with a frequency of 1. A cache with a capacity of 0 hands the new pair
+    /// straight back as evicted.
+    ///
+    /// [`Displaced::Replaced`]: enum.Displaced.html#variant.Replaced
+    /// [`Displaced::Evicted`]: enum.Displaced.html#variant.Evicted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::{Displaced, LfuCache};
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// assert_eq!(cache.put(1, 'a'), None);
+    /// assert_eq!(cache.put(2, 'b'), None);
+    /// assert_eq!(cache.put(1, 'c'), Some(Displaced::Replaced('a')));
+    /// assert_eq!(cache.put(3, 'd'), Some(Displaced::Evicted(2, 'b')));
+    /// ```
+    pub fn put(&mut self, k: K, v: V) -> Option<Displaced<K, V>> {
+        if let Some(value) = self.touch(&k) {
+            return Some(Displaced::Replaced(mem::replace(value, v)));
+        }
+        if self.capacity == 0 {
+            return Some(Displaced::Evicted(k, v));
+        }
+        let evicted = if self.entries.len() >= self.capacity {
+            self.pop_lfu()
+        } else {
+            None
+        };
+        self.bucket_after(0, 1).insert(k.clone());
+        self.entries.insert(k, (1, v));
+        evicted.map(|(k, v)| Displaced::Evicted(k, v))
+    }
+
+    /// Returns a reference to the value of the key and counts it as a use.
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.touch(k).map(|v| &*v)
+    }
+
+    /// Returns a mutable reference to the value of the key and counts it as a use.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.touch(k)
+    }
+
+    /// Returns a reference to the value of the key without counting it as a use.
+    #[inline]
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.entries.get(k).map(|(_, v)| v)
+    }
+
+    /// Returns `true` if the key is cached, without counting it as a use.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.entries.contains_key(k)
+    }
+
+    /// Returns how many times the key has been put or read, if it is cached.
+    #[inline]
+    pub fn frequency<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.entries.get(k).map(|&(freq, _)| freq)
+    }
+
+    /// Removes and returns the least frequently used entry, the oldest one among ties.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(3);
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// cache.put("c", 3);
+    /// cache.get(&"a");
+    /// assert_eq!(cache.pop_lfu(), Some(("b", 2)));
+    /// assert_eq!(cache.pop_lfu(), Some(("c", 3)));
+    /// assert_eq!(cache.pop_lfu(), Some(("a", 1)));
+    /// assert_eq!(cache.pop_lfu(), None);
+    /// ```
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let freq = self.min_freq;
+        let bucket = self
+            .buckets
+            .get_mut(&freq)
+            .expect("minimum frequency has a bucket");
+        let key = bucket.keys.pop_front().expect("buckets are never empty");
+        self.unlink_bucket(freq);
+        let (_, v) = self.entries.remove(&key).expect("bucketed keys are cached");
+        Some((key, v))
+    }
+
+    /// Removes a key from the cache, returning its value if it was cached.
+    pub fn pop<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (freq, v) = self.entries.remove(k)?;
+        let bucket = self
+            .buckets
+            .get_mut(&freq)
+            .expect("cached keys have a bucket");
+        bucket.keys.remove(k);
+        self.unlink_bucket(freq);
+        Some(v)
+    }
+
+    /// Moves a cached key to the next frequency bucket and returns its value.
+    fn touch<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (freq, _) = self.entries.get(k)?;
+        let freq = *freq;
+        let bucket = self
+            .buckets
+            .get_mut(&freq)
+            .expect("cached keys have a bucket");
+        let key = bucket
+            .keys
+            .take(k)
+            .expect("cached keys are in their bucket");
+        self.bucket_after(freq, freq + 1).insert(key);
+        self.unlink_bucket(freq);
+
+        let (freq, value) = self.entries.get_mut(k).expect("cached keys are present");
+        *freq += 1;
+        Some(value)
+    }
+
+    /// Returns the keys of `freq`, creating its bucket right after the bucket of `prev` if
+    /// needed. `prev` is the next lower frequency with a bucket, or 0 if there is none.
+    fn bucket_after(&mut self, prev: usize, freq: usize) -> &mut OrderedHashSet<K, S> {
+        if !self.buckets.contains_key(&freq) {
+            let next = if prev == 0 {
+                self.min_freq
+            } else {
+                mem::replace(&mut self.buckets[&prev].next, freq)
+            };
+            if prev == 0 {
+                self.min_freq = freq;
+            }
+            if next != 0 {
+                self.buckets[&next].prev = freq;
+            }
+            let keys = OrderedHashSet::with_hasher(self.hash_builder.clone()).unranked();
+            self.buckets.insert(freq, Bucket { keys, prev, next });
+        }
+        &mut self.buckets[&freq].keys
+    }
+
+    /// Drops the bucket of `freq` if it became empty, linking its neighbours together and
+    /// keeping `min_freq` up to date.
+    fn unlink_bucket(&mut self, freq: usize) {
+        if !self.buckets[&freq].keys.is_empty() {
+            return;
+        }
+        let Bucket { prev, next, .. } = self.buckets.remove(&freq).expect("bucket exists");
+        if prev == 0 {
+            self.min_freq = next;
+        } else {
+            self.buckets[&prev].next = next;
+        }
+        if next != 0 {
+            self.buckets[&next].prev = prev;
+        }
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for LfuCache<K, V, S>
+where
+    K: Eq + Hash + Clone,
+    S: BuildHasher + Clone,
+{
+    /// Puts every pair of `iter` into the cache in order, dropping evicted entries.
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.put(k, v);
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for LfuCache<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Formats the cache as `{k: v, ...}` in the order the keys were first inserted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> IntoIterator for LfuCache<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = iter::Map<map::IntoIter<K, (usize, V)>, fn((K, (usize, V))) -> (K, V)>;
+
+    /// Consumes the cache into its entries in the order their keys were first inserted.
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter().map(|(k, (_, v))| (k, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LfuCache;
+    use crate::lru::Displaced;
+
+    #[test]
+    fn evicts_least_frequent_then_oldest() {
+        let mut cache = LfuCache::new(3);
+        cache.extend(vec![("a", 1), ("b", 2), ("c", 3)]);
+        cache.get(&"a");
+        cache.get(&"c");
+        cache.get(&"b");
+        cache.get(&"c");
+
+        assert_eq!(cache.put("d", 4), Some(Displaced::Evicted("a", 1)));
+        assert_eq!(cache.put("e", 5), Some(Displaced::Evicted("d", 4)));
+        assert_eq!(cache.put("c", 6), Some(Displaced::Replaced(3)));
+        assert_eq!(cache.frequency(&"c"), Some(4));
+        assert_eq!(cache.peek(&"e"), Some(&5));
+        assert_eq!(cache.frequency(&"e"), Some(1));
+
+        assert_eq!(cache.pop_lfu(), Some(("e", 5)));
+        assert_eq!(cache.pop_lfu(), Some(("b", 2)));
+        assert_eq!(cache.pop_lfu(), Some(("c", 6)));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn pop_updates_minimum_frequency() {
+        let mut cache = LfuCache::new(3);
+        cache.put(1, ());
+        cache.put(2, ());
+        for _ in 0..3 {
+            cache.get(&2);
+        }
+        cache.put(3, ());
+        cache.get(&3);
+        assert_eq!(cache.pop(&1), Some(()));
+        assert_eq!(cache.pop(&4), None);
+
+        assert_eq!(cache.put(4, ()), None);
+        assert_eq!(cache.put(5, ()), Some(Displaced::Evicted(4, ())));
+        assert_eq!(cache.pop(&5), Some(()));
+        assert_eq!(cache.pop_lfu(), Some((3, ())));
+        assert_eq!(cache.pop_lfu(), Some((2, ())));
+        assert_eq!(cache.pop_lfu(), None);
+
+        assert_eq!(
+            LfuCache::new(0).put(1, 'a'),
+            Some(Displaced::Evicted(1, 'a'))
+        );
+        assert_eq!(LfuCache::new(usize::MAX).put(1, 'a'), None);
+    }
+
+    #[test]
+    fn matches_a_naive_model() {
+        // (key, frequency, step at which it reached that frequency)
+        let mut model: Vec<(u32, usize, usize)> = Vec::new();
+        let mut cache = LfuCache::new(8);
+        for step in 0..2000 {
+            let key = ((step * 7 + step / 13) % 20) as u32;
+            let cached = model.iter().position(|e| e.0 == key);
+            match step % 5 {
+                0..=2 => {
+                    assert_eq!(cache.get(&key).is_some(), cached.is_some());
+                    if let Some(i) = cached {
+                        model[i].1 += 1;
+                        model[i].2 = step;
+                    }
+                }
+                3 => {
+                    let displaced = cache.put(key, step);
+                    if let Some(i) = cached {
+                        model[i].1 += 1;
+                        model[i].2 = step;
+                        assert!(matches!(displaced, Some(Displaced::Replaced(_))));
+                        continue;
+                    }
+                    let evicted = if model.len() == 8 {
+                        let lfu = (0..8).min_by_key(|&i| (model[i].1, model[i].2)).unwrap();
+                        Some(model.remove(lfu).0)
+                    } else {
+                        None
+                    };
+                    model.push((key, 1, step));
+                    let displaced = displaced.map(|d| match d {
+                        Displaced::Evicted(k, _) => k,
+                        Displaced::Replaced(_) => panic!("{} was not cached", key),
+                    });
+                    assert_eq!(displaced, evicted);
+                }
+                _ => {
+                    assert_eq!(cache.pop(&key).is_some(), cached.is_some());
+                    if let Some(i) = cached {
+                        model.remove(i);
+                    }
+                }
+            }
+            assert_eq!(cache.len(), model.len());
+        }
+
+        model.sort_by_key(|e| (e.1, e.2));
+        for (key, freq, _) in model {
+            assert_eq!(cache.frequency(&key), Some(freq));
+            assert_eq!(cache.pop_lfu().map(|(k, _)| k), Some(key));
+        }
+        assert!(cache.is_empty());
+    }
+}
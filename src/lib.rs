@@ -1,3 +1,30 @@
+pub mod chain_map;
+pub mod counter;
+pub mod default_map;
+pub mod deque;
+pub mod lfu;
+pub mod lru;
 pub mod map;
+pub mod set;
+mod sorted;
+pub mod sorted_key_list;
+pub mod sorted_list;
+pub mod sorted_map;
+pub mod sorted_set;
+pub mod ttl;
+pub mod weighted;
 
+pub use self::chain_map::ChainMap;
+pub use self::counter::Counter;
+pub use self::default_map::DefaultOrderedHashMap;
+pub use self::deque::Deque;
+pub use self::lfu::LfuCache;
+pub use self::lru::{Displaced, LruCache};
 pub use self::map::OrderedHashMap;
+pub use self::set::OrderedHashSet;
+pub use self::sorted_key_list::{SortedKeyList, SortedListBy};
+pub use self::sorted_list::SortedList;
+pub use self::sorted_map::SortedMap;
+pub use self::sorted_set::SortedSet;
+pub use self::ttl::{Clock, SystemClock, TtlCache};
+pub use self::weighted::{Weigher, WeightedCache};
@@ -0,0 +1,413 @@
+use crate::map::{self, OrderedHashMap};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+/// A least-recently-used cache that keeps its entries in an `OrderedHashMap`, ordered from
+/// least to most recently used.
+///
+/// [`get`] and [`put`] move the entry to the end of the order with the map's amortized O(1)
+/// `move_to_end`, so an access costs one hash lookup and no allocation. When a [`put`] goes
+/// over capacity, the entry at the front is evicted and handed back to the caller as a
+/// [`Displaced::Evicted`].
+///
+/// [`get`]: #method.get
+/// [`put`]: #method.put
+/// [`Displaced::Evicted`]: enum.Displaced.html#variant.Evicted
+///
+/// # Examples
+///
+/// ```
+/// use ordered::{Displaced, LruCache};
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// assert_eq!(cache.get(&"a"), Some(&1));
+///
+/// // "b" is now the least recently used entry.
+/// assert_eq!(cache.put("c", 3), Some(Displaced::Evicted("b", 2)));
+/// let keys: Vec<_> = cache.iter().map(|(k, _)| *k).collect();
+/// assert_eq!(keys, ["a", "c"]);
+/// ```
+#[derive(Clone)]
+pub struct LruCache<K, V, S = RandomState> {
+    map: OrderedHashMap<K, V, S>,
+    capacity: usize,
+}
+
+/// What a cache's `put` pushed out to make room for the new pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Displaced<K, V> {
+    /// The key was already cached and this was its old value.
+    Replaced(V),
+    /// The cache was full and this entry was evicted. A cache with a capacity of 0 evicts the
+    /// new pair itself.
+    Evicted(K, V),
+}
+
+impl<K, V> LruCache<K, V, RandomState> {
+    /// Creates an empty `LruCache` that holds at most `capacity` entries.
+    #[inline]
+    pub fn new(capacity: usize) -> LruCache<K, V, RandomState> {
+        LruCache::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> LruCache<K, V, S> {
+    /// Creates an empty `LruCache` that holds at most `capacity` entries and uses the given
+    /// hash builder to hash keys.
+    #[inline]
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> LruCache<K, V, S> {
+        LruCache {
+            map: OrderedHashMap::with_hasher(hash_builder).unranked(),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of entries the cache holds.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the cache contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all entries from the cache.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns the least recently used entry without promoting it.
+    #[inline]
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.map.front()
+    }
+
+    /// Removes and returns the least recently used entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::LruCache;
+    ///
+    /// let mut cache = LruCache::new(3);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// cache.get(&1);
+    /// assert_eq!(cache.pop_lru(), Some((2, "b")));
+    /// assert_eq!(cache.pop_lru(), Some((1, "a")));
+    /// assert_eq!(cache.pop_lru(), None);
+    /// ```
+    #[inline]
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        self.map.pop_front()
+    }
+
+    /// Changes the capacity of the cache, evicting the least recently used entries if it
+    /// now holds too many.
+    ///
+    /// The evicted entries are returned from least to most recently used. They are removed
+    /// even if the iterator is dropped early.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::LruCache;
+    ///
+    /// let mut cache = LruCache::new(4);
+    /// cache.extend(vec![(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+    /// let evicted: Vec<_> = cache.resize(2).collect();
+    /// assert_eq!(evicted, [(1, 'a'), (2, 'b')]);
+    /// assert_eq!(cache.len(), 2);
+    /// ```
+    pub fn resize(&mut self, capacity: usize) -> map::Drain<'_, K, V> {
+        self.capacity = capacity;
+        let excess = self.map.len().saturating_sub(capacity);
+        self.map.drain(..excess)
+    }
+
+    /// An iterator over the entries from least to most recently used, without promoting
+    /// them.
+    #[inline]
+    pub fn iter(&self) -> map::Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    /// Unwraps the underlying map, ordered from least to most recently used.
+    #[inline]
+    pub fn into_map(self) -> OrderedHashMap<K, V, S> {
+        self.map.ranked()
+    }
+}
+
+impl<K, V, S> LruCache<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair and marks it as the most recently used entry.
+    ///
+    /// If the key was already cached, its value is replaced and the old value is returned as
+    /// [`Displaced::Replaced`]. Otherwise, if the cache was full, the least recently used
+    /// entry is evicted and returned as [`Displaced::Evicted`]. A cache with a capacity of 0
+    /// hands the new pair straight back as evicted.
+    ///
+    /// [`Displaced::Replaced`]: enum.Displaced.html#variant.Replaced
+    /// [`Displaced::Evicted`]: enum.Displaced.html#variant.Evicted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::{Displaced, LruCache};
+    ///
+    /// let mut cache = LruCache::new(1);
+    /// assert_eq!(cache.put("a", 1), None);
+    /// assert_eq!(cache.put("a", 2), Some(Displaced::Replaced(1)));
+    /// assert_eq!(cache.put("b", 3), Some(Displaced::Evicted("a", 2)));
+    /// ```
+    pub fn put(&mut self, k: K, v: V) -> Option<Displaced<K, V>> {
+        if let Some(value) = self.map.move_to_end_mut(&k) {
+            return Some(Displaced::Replaced(mem::replace(value, v)));
+        }
+        if self.capacity == 0 {
+            return Some(Displaced::Evicted(k, v));
+        }
+        let evicted = if self.map.len() >= self.capacity {
+            self.map.pop_front()
+        } else {
+            None
+        };
+        self.map.insert(k, v);
+        evicted.map(|(k, v)| Displaced::Evicted(k, v))
+    }
+
+    /// Returns a reference to the value of the key and marks it as the most recently used
+    /// entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// assert_eq!(cache.get(&"a"), Some(&1));
+    /// assert_eq!(cache.peek_lru(), Some((&"b", &2)));
+    /// ```
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.move_to_end_mut(k).map(|v| &*v)
+    }
+
+    /// Returns a mutable reference to the value of the key and marks it as the most recently
+    /// used entry.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.move_to_end_mut(k)
+    }
+
+    /// Returns a reference to the value of the key without promoting it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// assert_eq!(cache.peek(&"a"), Some(&1));
+    /// assert_eq!(cache.peek_lru(), Some((&"a", &1)));
+    /// ```
+    #[inline]
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(k)
+    }
+
+    /// Returns a mutable reference to the value of the key without promoting it.
+    #[inline]
+    pub fn peek_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get_mut(k)
+    }
+
+    /// Returns `true` if the key is cached, without promoting it.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.contains_key(k)
+    }
+
+    /// Removes a key from the cache, returning its value if it was cached.
+    #[inline]
+    pub fn pop<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.remove(k)
+    }
+
+    /// Puts every pair of `iter` into the cache in order and calls `on_evict` with each
+    /// entry that is evicted for lack of room. Replaced values are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// let mut evicted = Vec::new();
+    /// cache.put_all(vec![(1, 'a'), (2, 'b'), (2, 'B'), (3, 'c')], |k, v| evicted.push((k, v)));
+    /// assert_eq!(evicted, [(1, 'a')]);
+    /// ```
+    pub fn put_all<I, F>(&mut self, iter: I, mut on_evict: F)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        F: FnMut(K, V),
+    {
+        for (k, v) in iter {
+            if let Some(Displaced::Evicted(k, v)) = self.put(k, v) {
+                on_evict(k, v);
+            }
+        }
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for LruCache<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Puts every pair of `iter` into the cache in order, dropping evicted entries.
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.put_all(iter, |_, _| {});
+    }
+}
+
+impl<K, V, S> fmt::Debug for LruCache<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Formats the cache as `{k: v, ...}` from least to most recently used.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a LruCache<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = map::Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> map::Iter<'a, K, V> {
+        self.map.iter()
+    }
+}
+
+impl<K, V, S> IntoIterator for LruCache<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = map::IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> map::IntoIter<K, V> {
+        self.map.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Displaced, LruCache};
+
+    fn keys(cache: &LruCache<u32, u32>) -> Vec<u32> {
+        cache.iter().map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn recency_and_eviction() {
+        let mut cache = LruCache::new(3);
+        cache.extend((0..3).map(|i| (i, i * 10)));
+        assert_eq!(cache.get(&0), Some(&0));
+        assert_eq!(cache.peek(&1), Some(&10));
+        assert_eq!(keys(&cache), [1, 2, 0]);
+
+        assert_eq!(cache.put(3, 30), Some(Displaced::Evicted(1, 10)));
+        assert_eq!(cache.put(2, 21), Some(Displaced::Replaced(20)));
+        assert_eq!(keys(&cache), [0, 3, 2]);
+
+        *cache.get_mut(&0).unwrap() += 1;
+        assert_eq!(cache.pop(&3), Some(30));
+        assert_eq!(cache.put(4, 40), None);
+        assert_eq!(keys(&cache), [2, 0, 4]);
+        assert_eq!(cache.peek(&0), Some(&1));
+    }
+
+    #[test]
+    fn put_all_reports_only_evictions() {
+        let mut cache = LruCache::new(2);
+        let mut evicted = Vec::new();
+        cache.put_all(vec![(1, 1), (1, 2), (2, 3), (1, 4), (3, 5)], |k, v| {
+            evicted.push((k, v))
+        });
+        assert_eq!(evicted, [(2, 3)]);
+        assert_eq!(keys(&cache), [1, 3]);
+
+        let mut huge = LruCache::new(usize::MAX);
+        assert_eq!(huge.put(1, 1), None);
+    }
+
+    #[test]
+    fn resize_evicts_oldest() {
+        let mut cache = LruCache::new(5);
+        cache.extend((0..5).map(|i| (i, i)));
+        for i in 0..1000 {
+            cache.get(&(i % 2));
+        }
+        let evicted: Vec<_> = cache.resize(2).map(|(k, _)| k).collect();
+        assert_eq!(evicted, [2, 3, 4]);
+        assert_eq!(keys(&cache), [0, 1]);
+
+        assert_eq!(cache.resize(0).count(), 2);
+        assert_eq!(cache.put(9, 9), Some(Displaced::Evicted(9, 9)));
+        assert!(cache.is_empty());
+
+        assert_eq!(cache.resize(1).count(), 0);
+        assert_eq!(cache.put(9, 9), None);
+        assert_eq!(cache.peek_lru(), Some((&9, &9)));
+    }
+}
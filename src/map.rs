@@ -1,10 +1,30 @@
+mod core;
+mod entry;
+mod iter;
+mod ranks;
+
+pub use self::entry::{Entry, OccupiedEntry, VacantEntry};
+pub use self::iter::{Drain, IntoIter, Iter, IterMut, Keys, Values, ValuesMut};
+
+use self::core::OrderedCore;
+use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{BitOr, BitOrAssign, Index, IndexMut, RangeBounds};
 
+/// A hash map that remembers the order in which keys were first inserted, like a Python 3.7+
+/// `dict`.
+///
+/// Entries are stored once, in order, next to a table of indices into them, so insertion,
+/// lookup and removal are all amortized O(1).
 #[derive(Clone)]
 pub struct OrderedHashMap<K, V, S = RandomState> {
-    base: HashMap<K, V, S>,
-    order_list: Vec<K>,
+    hash_builder: S,
+    core: OrderedCore<K, V>,
 }
 
 impl<K, V> OrderedHashMap<K, V, RandomState> {
@@ -51,13 +71,13 @@ impl<K, V, S> OrderedHashMap<K, V, S> {
     ///
     /// let s = RandomState::new();
     /// let mut map = OrderedHashMap::<u32,u32>::with_hasher(s);
-    /// //map.insert(1, 2);
+    /// map.insert(1, 2);
     /// ```
     #[inline]
     pub fn with_hasher(hash_builder: S) -> OrderedHashMap<K, V, S> {
         OrderedHashMap {
-            base: HashMap::with_hasher(hash_builder),
-            order_list: Vec::<K>::new(),
+            hash_builder,
+            core: OrderedCore::new(),
         }
     }
     /// Creates an empty `OrderedHashMap` with the specified capacity, using `hash_builder`
@@ -72,13 +92,13 @@ impl<K, V, S> OrderedHashMap<K, V, S> {
     ///
     /// let s = RandomState::new();
     /// let mut map = OrderedHashMap::<u32, u32>::with_capacity_and_hasher(10, s);
-    /// //map.insert(1, 2);
+    /// map.insert(1, 2);
     /// ```
     #[inline]
     pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> OrderedHashMap<K, V, S> {
         OrderedHashMap {
-            base: HashMap::with_capacity_and_hasher(capacity, hash_builder),
-            order_list: Vec::<K>::with_capacity(capacity),
+            hash_builder,
+            core: OrderedCore::with_capacity(capacity),
         }
     }
 
@@ -87,29 +107,1952 @@ impl<K, V, S> OrderedHashMap<K, V, S> {
     /// # Examples
     ///
     /// ```
-    /// use std::collections::HashMap;
-    /// let map: HashMap<i32, i32> = HashMap::with_capacity(100);
+    /// use ordered::OrderedHashMap;
+    /// let map: OrderedHashMap<i32, i32> = OrderedHashMap::with_capacity(100);
     /// assert!(map.capacity() >= 100);
     /// ```
     #[inline]
     pub fn capacity(&self) -> usize {
-        self.order_list.capacity()
+        self.core.capacity()
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut a = OrderedHashMap::new();
+    /// assert_eq!(a.len(), 0);
+    /// a.insert(1, "a");
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut a = OrderedHashMap::new();
+    /// assert!(a.is_empty());
+    /// a.insert(1, "a");
+    /// assert!(!a.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.core.len() == 0
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated memory
+    /// for reuse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut a = OrderedHashMap::new();
+    /// a.insert(1, "a");
+    /// a.clear();
+    /// assert!(a.is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// An iterator visiting all key-value pairs in insertion order.
+    /// The iterator element type is `(&'a K, &'a V)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// let pairs: Vec<_> = map.iter().collect();
+    /// assert_eq!(pairs, [(&"a", &1), (&"b", &2), (&"c", &3)]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.core.slots().iter(),
+            remaining: self.core.len(),
+        }
+    }
+
+    /// An iterator visiting all key-value pairs in insertion order,
+    /// with mutable references to the values.
+    /// The iterator element type is `(&'a K, &'a mut V)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    ///
+    /// for (_, val) in map.iter_mut() {
+    ///     *val *= 2;
+    /// }
+    /// assert_eq!(map[&"a"], 2);
+    /// assert_eq!(map[&"b"], 4);
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let remaining = self.core.len();
+        IterMut {
+            slots: self.core.slots_mut().iter_mut(),
+            remaining,
+        }
+    }
+
+    /// An iterator visiting all keys in insertion order.
+    /// The iterator element type is `&'a K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("b", 2);
+    /// map.insert("a", 1);
+    ///
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"b", &"a"]);
+    /// ```
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// An iterator visiting all values in insertion order.
+    /// The iterator element type is `&'a V`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("b", 2);
+    /// map.insert("a", 1);
+    ///
+    /// let values: Vec<_> = map.values().collect();
+    /// assert_eq!(values, [&2, &1]);
+    /// ```
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// An iterator visiting all values mutably in insertion order.
+    /// The iterator element type is `&'a mut V`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    ///
+    /// for val in map.values_mut() {
+    ///     *val += 10;
+    /// }
+    /// let values: Vec<_> = map.values().collect();
+    /// assert_eq!(values, [&11, &12]);
+    /// ```
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    /// Returns the first key-value pair in the order, like peeking at Python's
+    /// `OrderedDict.popitem(last=False)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// assert_eq!(map.front(), None);
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert_eq!(map.front(), Some((&1, &"a")));
+    /// ```
+    #[inline]
+    pub fn front(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// Returns the first key-value pair in the order, with a mutable reference to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// if let Some((_, v)) = map.front_mut() {
+    ///     *v = "z";
+    /// }
+    /// assert_eq!(map[&1], "z");
+    /// ```
+    #[inline]
+    pub fn front_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.iter_mut().next()
+    }
+
+    /// Returns the last key-value pair in the order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// assert_eq!(map.back(), None);
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert_eq!(map.back(), Some((&2, &"b")));
+    /// ```
+    #[inline]
+    pub fn back(&self) -> Option<(&K, &V)> {
+        self.iter().next_back()
+    }
+
+    /// Returns the last key-value pair in the order, with a mutable reference to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// if let Some((_, v)) = map.back_mut() {
+    ///     *v = "z";
+    /// }
+    /// assert_eq!(map[&2], "z");
+    /// ```
+    #[inline]
+    pub fn back_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.iter_mut().next_back()
+    }
+
+    /// Removes and returns the first key-value pair in the order, like Python's
+    /// `OrderedDict.popitem(last=False)`.
+    ///
+    /// This is amortized O(1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert_eq!(map.pop_front(), Some((1, "a")));
+    /// assert_eq!(map.pop_front(), Some((2, "b")));
+    /// assert_eq!(map.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<(K, V)> {
+        if self.core.len() == 0 {
+            return None;
+        }
+        let bucket = self.core.remove(0);
+        Some((bucket.key, bucket.value))
+    }
+
+    /// Removes and returns the last key-value pair in the order, like Python's
+    /// `OrderedDict.popitem()`.
+    ///
+    /// This is amortized O(1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert_eq!(map.pop_back(), Some((2, "b")));
+    /// assert_eq!(map.pop_back(), Some((1, "a")));
+    /// assert_eq!(map.pop_back(), None);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<(K, V)> {
+        if self.core.len() == 0 {
+            return None;
+        }
+        let last = self.core.slots().len() - 1;
+        let bucket = self.core.remove(last);
+        Some((bucket.key, bucket.value))
+    }
+
+    /// Returns the key-value pair at `index` in the order.
+    ///
+    /// This is O(1) unless entries have been removed from the middle of the map since it was
+    /// last compacted, in which case it is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.get_index(1), Some((&"b", &2)));
+    /// assert_eq!(map.get_index(2), None);
+    /// ```
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        let bucket = self.core.bucket(self.core.slot_of(index)?);
+        Some((&bucket.key, &bucket.value))
+    }
+
+    /// Returns the key-value pair at `index` in the order, with a mutable reference to the
+    /// value.
+    ///
+    /// This has the same cost as [`get_index`].
+    ///
+    /// [`get_index`]: #method.get_index
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// if let Some((_, v)) = map.get_index_mut(0) {
+    ///     *v = 10;
+    /// }
+    /// assert_eq!(map[&"a"], 10);
+    /// ```
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        let slot = self.core.slot_of(index)?;
+        let bucket = self.core.bucket_mut(slot);
+        Some((&bucket.key, &mut bucket.value))
+    }
+
+    /// Removes the gaps left by entries removed from the middle of the map.
+    ///
+    /// This is O(n) when there are gaps. Afterwards [`get_index`] and [`get_index_of`] are O(1)
+    /// rather than O(log n) until the next removal from the middle.
+    ///
+    /// [`get_index`]: #method.get_index
+    /// [`get_index_of`]: #method.get_index_of
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<_, _> = (0..4).map(|i| (i, i * 10)).collect();
+    /// map.remove(&1);
+    /// map.compact();
+    /// assert_eq!(map.get_index(1), Some((&2, &20)));
+    /// assert_eq!(map.get_index_of(&3), Some(2));
+    /// ```
+    #[inline]
+    pub fn compact(&mut self) {
+        self.core.compact();
+    }
+
+    /// Stops tracking positions across gaps, for maps that are never read by position. Their
+    /// removals and moves then stay O(1), while positional reads walk the map.
+    #[inline]
+    pub(crate) fn unranked(mut self) -> Self {
+        self.core.set_ranked(false);
+        self
+    }
+
+    /// Tracks positions across gaps again, which is O(n) if the map has gaps.
+    #[inline]
+    pub(crate) fn ranked(mut self) -> Self {
+        self.core.set_ranked(true);
+        self
+    }
+
+    /// Swaps the positions of the entries at `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` are out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// map.swap_indices(0, 2);
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"c", &"b", &"a"]);
+    /// ```
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        let len = self.core.len();
+        assert!(
+            a < len && b < len,
+            "index out of bounds: the len is {}",
+            len
+        );
+        let slot_a = self.core.slot_of(a).expect("index is in bounds");
+        let slot_b = self.core.slot_of(b).expect("index is in bounds");
+        self.core.swap_slots(slot_a, slot_b);
+    }
+
+    /// Moves the entry at `from` to `to`, shifting the entries in between by one.
+    ///
+    /// This is O(n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` are out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// map.move_index(0, 1);
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"b", &"a", &"c"]);
+    /// ```
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        let len = self.core.len();
+        assert!(
+            from < len && to < len,
+            "index out of bounds: the len is {}",
+            len
+        );
+        self.core.compact();
+        self.core.move_slot(from, to);
+    }
+
+    /// Retains only the elements specified by the predicate, keeping their order.
+    ///
+    /// In other words, remove all pairs `(k, v)` such that `f(&k, &mut v)` returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<i32, i32> = (0..8).map(|x| (x, x * 10)).collect();
+    /// map.retain(|&k, _| k % 2 == 0);
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&0, &2, &4, &6]);
+    /// ```
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.core.retain(f);
+    }
+
+    /// Removes the key-value pairs at the positions in `range` and returns them in order.
+    ///
+    /// The range is counted in positions of the iteration order. The entries are removed even
+    /// if the iterator is not fully consumed; the entries after the range keep their relative
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the end point is
+    /// greater than the length of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// for (i, k) in ["a", "b", "c", "d"].iter().enumerate() {
+    ///     map.insert(*k, i);
+    /// }
+    ///
+    /// let drained: Vec<_> = map.drain(1..3).collect();
+    /// assert_eq!(drained, [("b", 1), ("c", 2)]);
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"a", &"d"]);
+    /// ```
+    #[inline]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, K, V>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (slots, remaining) = self.core.drain(range);
+        Drain { slots, remaining }
     }
 }
 
-impl<K, V, S> Default for OrderedHashMap<K, V, S>
+#[inline]
+fn make_hash<Q, S>(hash_builder: &S, k: &Q) -> u64
 where
-    S: Default,
+    Q: ?Sized + Hash,
+    S: BuildHasher,
 {
-    /// Creates an empty `OrderedHashMap<K, V, S>`, with the `Default` value for the hasher.
-    #[inline]
-    fn default() -> OrderedHashMap<K, V, S> {
-        OrderedHashMap::with_hasher(Default::default())
-    }
+    let mut state = hash_builder.build_hasher();
+    k.hash(&mut state);
+    state.finish()
 }
 
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {}
+impl<K, V, S> OrderedHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Reserves capacity for at least `additional` more elements to be inserted
+    /// in the `OrderedHashMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    /// let mut map: OrderedHashMap<&str, i32> = OrderedHashMap::new();
+    /// map.reserve(10);
+    /// assert!(map.capacity() >= 10);
+    /// ```
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.core.reserve(additional);
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<i32, i32> = OrderedHashMap::with_capacity(100);
+    /// map.insert(1, 2);
+    /// map.insert(3, 4);
+    /// assert!(map.capacity() >= 100);
+    /// map.shrink_to_fit();
+    /// assert!(map.capacity() >= 2);
+    /// ```
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.core.shrink_to_fit();
+    }
+
+    #[inline]
+    fn find<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        if self.core.len() == 0 {
+            return None;
+        }
+        self.core.find(make_hash(&self.hash_builder, k), k)
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, [`None`] is returned and the key is
+    /// appended to the end of the order.
+    ///
+    /// If the map did have this key present, the value is updated, and the old
+    /// value is returned. The key keeps its original position, the same as a Python
+    /// `dict`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.is_empty(), false);
+    ///
+    /// map.insert(37, "b");
+    /// assert_eq!(map.insert(37, "c"), Some("b"));
+    /// assert_eq!(map[&37], "c");
+    /// ```
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let hash = make_hash(&self.hash_builder, &k);
+        match self.core.find(hash, &k) {
+            Some(slot) => Some(mem::replace(&mut self.core.bucket_mut(slot).value, v)),
+            None => {
+                self.core.push(hash, k, v);
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if both maps hold the same key-value pairs, regardless of their order.
+    ///
+    /// This is how a Python `OrderedDict` compares with a plain `dict`; `==` between two
+    /// `OrderedHashMap`s also compares the order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut a = OrderedHashMap::new();
+    /// a.insert(1, "a");
+    /// a.insert(2, "b");
+    /// let mut b = OrderedHashMap::new();
+    /// b.insert(2, "b");
+    /// b.insert(1, "a");
+    ///
+    /// assert!(a != b);
+    /// assert!(a.eq_unordered(&b));
+    /// ```
+    pub fn eq_unordered<S2>(&self, other: &OrderedHashMap<K, V, S2>) -> bool
+    where
+        V: PartialEq,
+        S2: BuildHasher,
+    {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+
+    /// Inserts a key-value pair at `index` in the order and returns the resulting index along
+    /// with the old value, if any.
+    ///
+    /// If the key is new, the entries from `index` onwards shift back by one, so `index` may be
+    /// equal to the length of the map. If the key is already present, its value is replaced and
+    /// the entry is moved to `index`, so `index` must be less than the length of the map.
+    /// Either way the entry ends up at `index`.
+    ///
+    /// This is O(n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("c", 3);
+    ///
+    /// assert_eq!(map.insert_at(1, "b", 2), (1, None));
+    /// assert_eq!(map.insert_at(0, "c", 30), (0, Some(3)));
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"c", &"a", &"b"]);
+    /// ```
+    pub fn insert_at(&mut self, index: usize, k: K, v: V) -> (usize, Option<V>) {
+        let hash = make_hash(&self.hash_builder, &k);
+        match self.core.find(hash, &k) {
+            Some(slot) => {
+                let len = self.core.len();
+                assert!(
+                    index < len,
+                    "index {} is out of bounds for length {}",
+                    index,
+                    len
+                );
+                let from = self.core.position_of(slot);
+                let old = mem::replace(&mut self.core.bucket_mut(slot).value, v);
+                self.core.compact();
+                self.core.move_slot(from, index);
+                (index, Some(old))
+            }
+            None => {
+                self.core.insert_at(index, hash, k, v);
+                (index, None)
+            }
+        }
+    }
+
+    /// Inserts a key-value pair right before `anchor` in the order and returns the resulting
+    /// index along with the old value, if any.
+    ///
+    /// If the key is already present, its value is replaced and the entry is moved before the
+    /// anchor; if the key is the anchor itself, only the value is replaced. If the anchor is
+    /// not in the map, nothing is inserted and the key-value pair is handed back as an error.
+    ///
+    /// This is O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("c", 3);
+    ///
+    /// assert_eq!(map.insert_before(&"c", "b", 2), Ok((1, None)));
+    /// assert_eq!(map.insert_before(&"z", "d", 4), Err(("d", 4)));
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"a", &"b", &"c"]);
+    /// ```
+    pub fn insert_before<Q>(&mut self, anchor: &Q, k: K, v: V) -> Result<(usize, Option<V>), (K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let anchor_index = match self.get_index_of(anchor) {
+            Some(index) => index,
+            None => return Err((k, v)),
+        };
+        let index = match self.get_index_of::<K>(&k) {
+            Some(from) if from < anchor_index => anchor_index - 1,
+            _ => anchor_index,
+        };
+        Ok(self.insert_at(index, k, v))
+    }
+
+    /// Inserts a key-value pair right after `anchor` in the order and returns the resulting
+    /// index along with the old value, if any.
+    ///
+    /// If the key is already present, its value is replaced and the entry is moved after the
+    /// anchor; if the key is the anchor itself, only the value is replaced. If the anchor is
+    /// not in the map, nothing is inserted and the key-value pair is handed back as an error.
+    ///
+    /// This is O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("c", 3);
+    ///
+    /// assert_eq!(map.insert_after(&"a", "b", 2), Ok((1, None)));
+    /// assert_eq!(map.insert_after(&"c", "a", 10), Ok((2, Some(1))));
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"b", &"c", &"a"]);
+    /// ```
+    pub fn insert_after<Q>(&mut self, anchor: &Q, k: K, v: V) -> Result<(usize, Option<V>), (K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let anchor_index = match self.get_index_of(anchor) {
+            Some(index) => index,
+            None => return Err((k, v)),
+        };
+        let index = match self.get_index_of::<K>(&k) {
+            Some(from) if from <= anchor_index => anchor_index,
+            _ => anchor_index + 1,
+        };
+        Ok(self.insert_at(index, k, v))
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut letters = OrderedHashMap::new();
+    ///
+    /// for ch in "a short treatise on fungi".chars() {
+    ///     *letters.entry(ch).or_insert(0) += 1;
+    /// }
+    ///
+    /// assert_eq!(letters[&'s'], 2);
+    /// assert_eq!(letters[&'t'], 3);
+    /// assert_eq!(letters.get_index(0), Some((&'a', &2)));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let hash = make_hash(&self.hash_builder, &key);
+        match self.core.find(hash, &key) {
+            Some(slot) => Entry::Occupied(OccupiedEntry {
+                core: &mut self.core,
+                slot,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                core: &mut self.core,
+                hash,
+                key,
+            }),
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
+    /// the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.find(k).map(|slot| &self.core.bucket(slot).value)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get_key_value(&1), Some((&1, &"a")));
+    /// assert_eq!(map.get_key_value(&2), None);
+    /// ```
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let bucket = self.core.bucket(self.find(k)?);
+        Some((&bucket.key, &bucket.value))
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// if let Some(x) = map.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(map[&1], "b");
+    /// ```
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = self.find(k)?;
+        Some(&mut self.core.bucket_mut(slot).value)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.contains_key(&1), true);
+    /// assert_eq!(map.contains_key(&2), false);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.find(k).is_some()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    ///
+    /// This is the same as [`shift_remove`]: the remaining keys keep their relative order, as
+    /// with `del` on a Python `dict`.
+    ///
+    /// [`shift_remove`]: #method.shift_remove
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.shift_remove(k)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map. The remaining keys keep their relative order.
+    ///
+    /// Unlike shifting a `Vec`, this is amortized O(1): the entry leaves a vacant slot behind,
+    /// and vacant slots are compacted in bulk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// map.insert(3, "c");
+    /// assert_eq!(map.shift_remove(&1), Some("a"));
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&2, &3]);
+    /// ```
+    #[inline]
+    pub fn shift_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.shift_remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the
+    /// key was previously in the map. The remaining keys keep their relative order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.shift_remove_entry(&1), Some((1, "a")));
+    /// assert_eq!(map.shift_remove_entry(&1), None);
+    /// ```
+    pub fn shift_remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = self.find(k)?;
+        let bucket = self.core.remove(slot);
+        Some((bucket.key, bucket.value))
+    }
+
+    /// Removes a key from the map, returning its position along with the stored key and value
+    /// if the key was previously in the map. The remaining keys keep their relative order.
+    ///
+    /// Finding the position is O(1) unless entries have been removed from the middle of the
+    /// map since it was last compacted, in which case it is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert_eq!(map.shift_remove_full(&2), Some((1, 2, "b")));
+    /// assert_eq!(map.shift_remove_full(&2), None);
+    /// ```
+    pub fn shift_remove_full<Q>(&mut self, k: &Q) -> Option<(usize, K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = self.find(k)?;
+        let index = self.core.position_of(slot);
+        let bucket = self.core.remove(slot);
+        Some((index, bucket.key, bucket.value))
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    ///
+    /// The last entry of the map takes the place of the removed one, which disturbs the order
+    /// but is O(1). Use [`shift_remove`] to keep the order.
+    ///
+    /// [`shift_remove`]: #method.shift_remove
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// map.insert(3, "c");
+    /// assert_eq!(map.swap_remove(&1), Some("a"));
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&3, &2]);
+    /// ```
+    #[inline]
+    pub fn swap_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.swap_remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the
+    /// key was previously in the map. The last entry of the map takes its place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.swap_remove_entry(&1), Some((1, "a")));
+    /// assert_eq!(map.swap_remove_entry(&1), None);
+    /// ```
+    pub fn swap_remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = self.find(k)?;
+        let bucket = self.core.swap_remove(slot);
+        Some((bucket.key, bucket.value))
+    }
+
+    /// Removes a key from the map, returning its position along with the stored key and value
+    /// if the key was previously in the map. The last entry of the map takes its place, so
+    /// after the call the returned position holds what used to be the last entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// map.insert(3, "c");
+    /// assert_eq!(map.swap_remove_full(&1), Some((0, 1, "a")));
+    /// assert_eq!(map.get_index(0), Some((&3, &"c")));
+    /// ```
+    pub fn swap_remove_full<Q>(&mut self, k: &Q) -> Option<(usize, K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = self.find(k)?;
+        let index = self.core.position_of(slot);
+        let bucket = self.core.swap_remove(slot);
+        Some((index, bucket.key, bucket.value))
+    }
+
+    /// Returns the position of the key in the order.
+    ///
+    /// This is O(1) unless entries have been removed from the middle of the map since it was
+    /// last compacted, in which case it is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.get_index_of(&"b"), Some(1));
+    /// assert_eq!(map.get_index_of(&"z"), None);
+    /// ```
+    pub fn get_index_of<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.find(k).map(|slot| self.core.position_of(slot))
+    }
+
+    /// Moves an existing key to the end of the order, like Python's
+    /// `OrderedDict.move_to_end(key)`.
+    ///
+    /// Returns `true` if the key was present. This is amortized O(1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// assert!(map.move_to_end(&"a"));
+    /// assert!(!map.move_to_end(&"z"));
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"b", &"c", &"a"]);
+    /// ```
+    pub fn move_to_end<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        match self.find(k) {
+            Some(slot) => {
+                self.core.move_to_back(slot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves an existing key to the front of the order, like Python's
+    /// `OrderedDict.move_to_end(key, last=False)`.
+    ///
+    /// Returns `true` if the key was present. This is amortized O(1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// assert!(map.move_to_front(&"c"));
+    /// assert!(!map.move_to_front(&"z"));
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"c", &"a", &"b"]);
+    /// ```
+    pub fn move_to_front<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        match self.find(k) {
+            Some(slot) => {
+                self.core.move_to_front(slot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves an existing key to the end of the order and returns its value, hashing the key
+    /// only once.
+    pub(crate) fn move_to_end_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = self.find(k)?;
+        let slot = self.core.move_to_back(slot);
+        Some(&mut self.core.bucket_mut(slot).value)
+    }
+}
+
+impl<K, V, S1, S2> PartialEq<OrderedHashMap<K, V, S2>> for OrderedHashMap<K, V, S1>
+where
+    K: PartialEq,
+    V: PartialEq,
+{
+    /// Compares the entries of both maps in order, like `OrderedDict == OrderedDict` in
+    /// Python. Use [`eq_unordered`] to ignore the order.
+    ///
+    /// [`eq_unordered`]: #method.eq_unordered
+    fn eq(&self, other: &OrderedHashMap<K, V, S2>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K, V, S> Eq for OrderedHashMap<K, V, S>
+where
+    K: Eq,
+    V: Eq,
+{
+}
+
+impl<K, V, S1, S2> PartialEq<HashMap<K, V, S2>> for OrderedHashMap<K, V, S1>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S2: BuildHasher,
+{
+    /// Compares the entries of both maps ignoring the order, like `OrderedDict == dict` in
+    /// Python.
+    fn eq(&self, other: &HashMap<K, V, S2>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V, S1, S2> PartialEq<OrderedHashMap<K, V, S2>> for HashMap<K, V, S1>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S1: BuildHasher,
+{
+    /// Compares the entries of both maps ignoring the order, like `dict == OrderedDict` in
+    /// Python.
+    fn eq(&self, other: &OrderedHashMap<K, V, S2>) -> bool {
+        other == self
+    }
+}
+
+impl<K, V, S> Hash for OrderedHashMap<K, V, S>
+where
+    K: Hash,
+    V: Hash,
+{
+    /// Hashes the entries in order, so maps that are equal hash the same.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for (k, v) in self {
+            k.hash(state);
+            v.hash(state);
+        }
+    }
+}
+
+impl<K, V, S> BitOr<&OrderedHashMap<K, V, S>> for &OrderedHashMap<K, V, S>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+    S: BuildHasher + Default,
+{
+    type Output = OrderedHashMap<K, V, S>;
+
+    /// Returns the merge of `self` and `rhs` as a new `OrderedHashMap<K, V, S>`, like Python's
+    /// `d1 | d2`.
+    ///
+    /// The keys of `self` keep their order, the values of `rhs` win, and the keys only in
+    /// `rhs` are appended in its order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let a = OrderedHashMap::from([("x", 1), ("y", 2)]);
+    /// let b = OrderedHashMap::from([("z", 3), ("x", 10)]);
+    ///
+    /// let merged = &a | &b;
+    /// assert_eq!(format!("{:?}", merged), r#"{"x": 10, "y": 2, "z": 3}"#);
+    /// ```
+    fn bitor(self, rhs: &OrderedHashMap<K, V, S>) -> OrderedHashMap<K, V, S> {
+        let mut map =
+            OrderedHashMap::with_capacity_and_hasher(self.len() + rhs.len(), Default::default());
+        map.extend(self.iter().map(|(k, v)| (k.clone(), v.clone())));
+        map |= rhs;
+        map
+    }
+}
+
+impl<K, V, S1, S2> BitOrAssign<&OrderedHashMap<K, V, S2>> for OrderedHashMap<K, V, S1>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+    S1: BuildHasher,
+{
+    /// Merges `rhs` into `self`, like Python's `d1 |= d2`.
+    ///
+    /// The keys of `self` keep their order, the values of `rhs` win, and the keys only in
+    /// `rhs` are appended in its order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut a = OrderedHashMap::from([("x", 1), ("y", 2)]);
+    /// a |= &OrderedHashMap::from([("z", 3), ("x", 10)]);
+    /// assert_eq!(format!("{:?}", a), r#"{"x": 10, "y": 2, "z": 3}"#);
+    /// ```
+    fn bitor_assign(&mut self, rhs: &OrderedHashMap<K, V, S2>) {
+        self.extend(rhs.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+}
+
+impl<K, V, S1, S2> BitOrAssign<OrderedHashMap<K, V, S2>> for OrderedHashMap<K, V, S1>
+where
+    K: Eq + Hash,
+    S1: BuildHasher,
+{
+    /// Merges `rhs` into `self` without cloning, like Python's `d1 |= d2`.
+    #[inline]
+    fn bitor_assign(&mut self, rhs: OrderedHashMap<K, V, S2>) {
+        self.extend(rhs);
+    }
+}
+
+impl<K, V, S> Default for OrderedHashMap<K, V, S>
+where
+    S: Default,
+{
+    /// Creates an empty `OrderedHashMap<K, V, S>`, with the `Default` value for the hasher.
+    #[inline]
+    fn default() -> OrderedHashMap<K, V, S> {
+        OrderedHashMap::with_hasher(Default::default())
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for OrderedHashMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: ?Sized + Eq + Hash,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the `OrderedHashMap`.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, Q, V, S> IndexMut<&Q> for OrderedHashMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: ?Sized + Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns a mutable reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the `OrderedHashMap`.
+    #[inline]
+    fn index_mut(&mut self, key: &Q) -> &mut V {
+        self.get_mut(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S> fmt::Debug for OrderedHashMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Formats the map as `{k: v, ...}` in insertion order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for OrderedHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    /// Creates a map from the key-value pairs in order. A repeated key keeps the position of
+    /// its first occurrence and the value of its last, as with Python's `dict(pairs)`.
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> OrderedHashMap<K, V, S> {
+        let mut map = OrderedHashMap::with_hasher(Default::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for OrderedHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Inserts every key-value pair in order, with the same rules as [`insert`].
+    ///
+    /// [`insert`]: #method.insert
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        let lower = iter.size_hint().0;
+        let reserve = if self.is_empty() {
+            lower
+        } else {
+            lower / 2 + lower % 2
+        };
+        self.reserve(reserve);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for OrderedHashMap<K, V, S>
+where
+    K: Eq + Hash + Copy,
+    V: Copy,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|(&k, &v)| (k, v)));
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for OrderedHashMap<K, V, RandomState>
+where
+    K: Eq + Hash,
+{
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let map: OrderedHashMap<_, _> = OrderedHashMap::from([(2, "b"), (1, "a")]);
+    /// assert_eq!(format!("{:?}", map), r#"{2: "b", 1: "a"}"#);
+    /// ```
+    fn from(arr: [(K, V); N]) -> Self {
+        IntoIterator::into_iter(arr).collect()
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for OrderedHashMap<K, V, RandomState>
+where
+    K: Eq + Hash,
+{
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let map: OrderedHashMap<_, _> = OrderedHashMap::from(vec![("b", 2), ("a", 1), ("b", 3)]);
+    /// assert_eq!(format!("{:?}", map), r#"{"b": 3, "a": 1}"#);
+    /// ```
+    fn from(vec: Vec<(K, V)>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<K, V, S> Index<usize> for OrderedHashMap<K, V, S> {
+    type Output = V;
+
+    /// Returns a reference to the value at the supplied position in the order.
+    ///
+    /// This has the same cost as [`get_index`]: O(1), or O(log n) if entries have been removed
+    /// from the middle since the map was last compacted.
+    ///
+    /// [`get_index`]: struct.OrderedHashMap.html#method.get_index
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &V {
+        self.get_index(index).expect("index out of bounds").1
+    }
+}
+
+impl<K, V, S> IndexMut<usize> for OrderedHashMap<K, V, S> {
+    /// Returns a mutable reference to the value at the supplied position in the order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut V {
+        self.get_index_mut(index).expect("index out of bounds").1
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a OrderedHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut OrderedHashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S> IntoIterator for OrderedHashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Creates a consuming iterator, that is, one that moves each key-value
+    /// pair out of the map in insertion order. The map cannot be used after
+    /// calling this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    ///
+    /// let vec: Vec<(&str, i32)> = map.into_iter().collect();
+    /// assert_eq!(vec, [("a", 1), ("b", 2)]);
+    /// ```
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V> {
+        let remaining = self.core.len();
+        IntoIter {
+            slots: self.core.into_slots().into_iter(),
+            remaining,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderedHashMap;
+    use std::hash::{BuildHasher, Hash};
+
+    fn keys<K: Clone, V, S>(map: &OrderedHashMap<K, V, S>) -> Vec<K> {
+        map.core
+            .slots()
+            .iter()
+            .flatten()
+            .map(|b| b.key.clone())
+            .collect()
+    }
+
+    fn assert_consistent<K, V, S>(map: &OrderedHashMap<K, V, S>)
+    where
+        K: Eq + Hash,
+        S: BuildHasher,
+    {
+        let mut count = 0;
+        for (k, _) in map {
+            assert!(map.contains_key(k));
+            count += 1;
+        }
+        assert_eq!(count, map.len());
+        assert_eq!(map.iter().len(), map.len());
+    }
+
+    #[test]
+    fn it_works() {}
+
+    #[test]
+    fn reinsert_keeps_position() {
+        let mut map = OrderedHashMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        map.insert("c", 3);
+        assert_eq!(map.insert("a", 10), Some(1));
+        assert_eq!(keys(&map), vec!["a", "b", "c"]);
+        assert_eq!(map.get("a"), Some(&10));
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn remove_keeps_order_in_sync() {
+        let mut map = OrderedHashMap::new();
+        for i in 0..5 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.remove(&2), Some(20));
+        assert_eq!(map.remove(&2), None);
+        assert_eq!(keys(&map), vec![0, 1, 3, 4]);
+        assert_consistent(&map);
+
+        map.insert(2, 200);
+        assert_eq!(keys(&map), vec![0, 1, 3, 4, 2]);
+        assert!(map.contains_key(&2));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(keys(&map).is_empty());
+    }
+
+    #[test]
+    fn remove_many_from_large_map() {
+        let n = 200_000;
+        let mut map = OrderedHashMap::with_capacity(n);
+        for i in 0..n {
+            map.insert(i, i);
+        }
+        for i in (0..n).filter(|i| i % 3 != 0) {
+            assert_eq!(map.remove(&i), Some(i));
+        }
+        assert_eq!(map.len(), n / 3 + 1);
+        assert_eq!(keys(&map), (0..n).step_by(3).collect::<Vec<_>>());
+        assert_consistent(&map);
+    }
+
+    #[test]
+    fn iterators_skip_removed_entries() {
+        let mut map = OrderedHashMap::new();
+        for i in 0..8 {
+            map.insert(i, i * 10);
+        }
+        map.remove(&2);
+        map.remove(&5);
+
+        let mut iter = map.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next(), Some((&0, &0)));
+        assert_eq!(iter.next_back(), Some((&7, &70)));
+        assert_eq!(iter.len(), 4);
+        let middle: Vec<_> = iter.map(|(k, _)| *k).collect();
+        assert_eq!(middle, vec![1, 3, 4, 6]);
+
+        let reversed: Vec<_> = map.values().rev().cloned().collect();
+        assert_eq!(reversed, vec![70, 60, 40, 30, 10, 0]);
+
+        for (k, v) in &mut map {
+            *v += k;
+        }
+        let owned: Vec<_> = map.into_iter().collect();
+        assert_eq!(
+            owned,
+            vec![(0, 0), (1, 11), (3, 33), (4, 44), (6, 66), (7, 77)]
+        );
+    }
+
+    #[test]
+    fn drain_skips_removed_entries() {
+        let mut map = OrderedHashMap::new();
+        for i in 0..8 {
+            map.insert(i, i);
+        }
+        map.remove(&1);
+        map.remove(&3);
+
+        let drained: Vec<_> = map.drain(1..=2).rev().collect();
+        assert_eq!(drained, vec![(4, 4), (2, 2)]);
+        assert_eq!(keys(&map), vec![0, 5, 6, 7]);
+        assert_consistent(&map);
+
+        {
+            let mut drain = map.drain(..);
+            assert_eq!(drain.next(), Some((0, 0)));
+        }
+        assert!(map.is_empty());
+        map.insert(9, 9);
+        assert_eq!(keys(&map), vec![9]);
+    }
+
+    #[test]
+    fn move_to_end_and_front_as_recency_list() {
+        let mut map = OrderedHashMap::new();
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        for round in 0..10_000 {
+            let key = (round * 7) % 1000;
+            assert!(map.move_to_end(&key));
+            assert_eq!(map.keys().next_back(), Some(&key));
+        }
+        for key in (0..1000).step_by(5) {
+            assert!(map.move_to_front(&key));
+            assert_eq!(map.keys().next(), Some(&key));
+        }
+        assert_eq!(map.len(), 1000);
+        assert_consistent(&map);
+        assert!(!map.move_to_end(&1000));
+        assert!(!map.move_to_front(&1000));
+    }
+
+    #[test]
+    fn fifo_queue_with_random_removal() {
+        let mut map = OrderedHashMap::new();
+        let mut next_id = 0;
+        let mut popped = Vec::new();
+        for round in 0..2000 {
+            map.insert(next_id, round);
+            next_id += 1;
+            map.insert(next_id, round);
+            next_id += 1;
+            if round % 5 == 0 {
+                map.remove(&(next_id - 1));
+            }
+            if let Some((id, _)) = map.pop_front() {
+                popped.push(id);
+            }
+        }
+        let mut sorted = popped.clone();
+        sorted.sort();
+        assert_eq!(popped, sorted);
+        assert_eq!(map.front().map(|(k, _)| *k), map.keys().next().cloned());
+        assert_eq!(map.back().map(|(k, _)| *k), map.keys().next_back().cloned());
+        assert_consistent(&map);
+
+        while map.pop_back().is_some() {}
+        assert!(map.is_empty());
+        assert_eq!(map.front(), None);
+        assert_eq!(map.back_mut(), None);
+    }
+
+    #[test]
+    fn positional_access_after_removals() {
+        let mut map = OrderedHashMap::new();
+        for i in 0..10 {
+            map.insert(i, i * 10);
+        }
+        map.remove(&3);
+        map.remove(&6);
+
+        let expected = [0, 1, 2, 4, 5, 7, 8, 9];
+        for (index, key) in expected.iter().enumerate() {
+            assert_eq!(map.get_index(index), Some((key, &(key * 10))));
+            assert_eq!(map.get_index_of(key), Some(index));
+            assert_eq!(map[index], key * 10);
+        }
+        assert_eq!(map.get_index(expected.len()), None);
+        assert_eq!(map.get_index_of(&3), None);
+
+        map[3] += 1;
+        assert_eq!(map[&4], 41);
+
+        map.swap_indices(0, 7);
+        map.move_index(7, 1);
+        assert_eq!(keys(&map), vec![9, 0, 1, 2, 4, 5, 7, 8]);
+        map.move_index(1, 7);
+        assert_eq!(keys(&map), vec![9, 1, 2, 4, 5, 7, 8, 0]);
+        assert_consistent(&map);
+    }
+
+    #[test]
+    #[should_panic]
+    fn swap_indices_out_of_bounds() {
+        let mut map = OrderedHashMap::new();
+        map.insert(1, 1);
+        map.swap_indices(0, 1);
+    }
+
+    #[test]
+    fn entry_counts_and_reorders() {
+        use super::Entry;
+
+        let mut counts = OrderedHashMap::new();
+        for word in "b a c a b a d".split(' ') {
+            counts.entry(word).and_modify(|c| *c += 1).or_insert(1);
+        }
+        let pairs: Vec<_> = counts.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(pairs, vec![("b", 2), ("a", 3), ("c", 1), ("d", 1)]);
+
+        counts.remove(&"c");
+        match counts.entry("d") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.index(), 2);
+                entry.move_to_front();
+                assert_eq!(entry.index(), 0);
+                assert_eq!(entry.insert(5), 1);
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match counts.entry("e") {
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.index(), 3);
+                *entry.insert_at(1, 0) += 7;
+            }
+            Entry::Occupied(_) => unreachable!(),
+        }
+        assert_eq!(keys(&counts), vec!["d", "e", "b", "a"]);
+        assert_eq!(counts[&"e"], 7);
+
+        if let Entry::Occupied(entry) = counts.entry("b") {
+            assert_eq!(entry.remove_entry(), ("b", 2));
+        }
+        assert_eq!(*counts.entry("z").or_default(), 0);
+        assert_eq!(keys(&counts), vec!["d", "e", "a", "z"]);
+        assert_consistent(&counts);
+    }
+
+    #[test]
+    fn shift_and_swap_remove() {
+        let mut map = OrderedHashMap::new();
+        for i in 0..8 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.shift_remove_full(&2), Some((2, 2, 2)));
+        assert_eq!(keys(&map), vec![0, 1, 3, 4, 5, 6, 7]);
+        assert_eq!(map.swap_remove_full(&1), Some((1, 1, 1)));
+        assert_eq!(keys(&map), vec![0, 7, 3, 4, 5, 6]);
+        assert_eq!(map.swap_remove_entry(&6), Some((6, 6)));
+        assert_eq!(keys(&map), vec![0, 7, 3, 4, 5]);
+        assert_eq!(map.shift_remove_entry(&0), Some((0, 0)));
+        assert_eq!(map.swap_remove(&0), None);
+        assert_eq!(map.shift_remove_full(&0), None);
+        assert_eq!(keys(&map), vec![7, 3, 4, 5]);
+        for (index, key) in [7, 3, 4, 5].iter().enumerate() {
+            assert_eq!(map.get_index_of(key), Some(index));
+        }
+        assert_consistent(&map);
+    }
+
+    #[test]
+    fn insert_relative_to_anchor() {
+        let mut map = OrderedHashMap::new();
+        for k in ["a", "b", "c", "d"].iter() {
+            map.insert(k.to_string(), 0);
+        }
+        map.remove("b");
+
+        assert_eq!(map.insert_after("a", "x".to_string(), 1), Ok((1, None)));
+        assert_eq!(map.insert_before("a", "y".to_string(), 2), Ok((0, None)));
+        assert_eq!(keys(&map), vec!["y", "a", "x", "c", "d"]);
+
+        assert_eq!(map.insert_after("d", "y".to_string(), 3), Ok((4, Some(2))));
+        assert_eq!(map.insert_before("a", "d".to_string(), 4), Ok((0, Some(0))));
+        assert_eq!(keys(&map), vec!["d", "a", "x", "c", "y"]);
+
+        assert_eq!(map.insert_before("c", "c".to_string(), 5), Ok((3, Some(0))));
+        assert_eq!(map.insert_after("x", "x".to_string(), 6), Ok((2, Some(1))));
+        assert_eq!(map.insert_at(5, "z".to_string(), 7), (5, None));
+        assert_eq!(
+            map.insert_after("q", "w".to_string(), 8),
+            Err(("w".to_string(), 8))
+        );
+        assert_eq!(keys(&map), vec!["d", "a", "x", "c", "y", "z"]);
+        let values: Vec<_> = map.values().cloned().collect();
+        assert_eq!(values, vec![4, 0, 6, 5, 3, 7]);
+        assert_consistent(&map);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_at_existing_key_out_of_bounds() {
+        let mut map = OrderedHashMap::new();
+        map.insert(1, 1);
+        map.insert(2, 2);
+        map.insert_at(2, 1, 1);
+    }
+
+    #[test]
+    fn equality_is_order_sensitive() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashMap;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of<T: Hash>(value: &T) -> u64 {
+            let mut state = DefaultHasher::new();
+            value.hash(&mut state);
+            state.finish()
+        }
+
+        let mut a = OrderedHashMap::new();
+        let mut b = OrderedHashMap::new();
+        for i in 0..5 {
+            a.insert(i, i * 2);
+            b.insert(4 - i, (4 - i) * 2);
+        }
+        assert!(a != b);
+        assert!(a.eq_unordered(&b));
+        assert!(b.eq_unordered(&a));
+
+        b.move_to_end(&4);
+        b.move_to_front(&0);
+        b.swap_indices(1, 3);
+        b.remove(&2);
+        b.insert_at(2, 2, 4);
+        assert!(a == b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        b.insert(2, 5);
+        assert!(a != b);
+        assert!(!a.eq_unordered(&b));
+
+        let plain: HashMap<_, _> = (0..5).map(|i| (i, i * 2)).collect();
+        assert!(a == plain);
+        assert!(plain == a);
+        a.remove(&0);
+        assert!(a != plain);
+    }
+
+    #[test]
+    fn build_extend_and_debug() {
+        let mut map: OrderedHashMap<&str, i32> = [("x", 1), ("y", 2)].iter().cloned().collect();
+        assert_eq!(format!("{:?}", map), r#"{"x": 1, "y": 2}"#);
+
+        map.extend(vec![("z", 3), ("x", 10)]);
+        let extra = OrderedHashMap::from([("w", 4), ("y", 20)]);
+        map.extend(&extra);
+        assert_eq!(
+            format!("{:?}", map),
+            r#"{"x": 10, "y": 20, "z": 3, "w": 4}"#
+        );
+
+        map["z"] += 30;
+        assert_eq!(map["z"], 33);
+        map.remove("y");
+        assert_eq!(format!("{:?}", map), r#"{"x": 10, "z": 33, "w": 4}"#);
+        assert_eq!(
+            map,
+            OrderedHashMap::from(vec![("x", 10), ("z", 33), ("w", 4)])
+        );
+
+        let empty: OrderedHashMap<i32, i32> = OrderedHashMap::new();
+        assert_eq!(format!("{:?}", empty), "{}");
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_mut_missing_key() {
+        let mut map = OrderedHashMap::from([(1, 1)]);
+        map[&2] = 2;
+    }
+
+    #[test]
+    fn merge_operators() {
+        let mut a = OrderedHashMap::from([(1, "a"), (2, "b"), (3, "c")]);
+        a.remove(&2);
+        let b = OrderedHashMap::from([(4, "D"), (1, "A"), (2, "B")]);
+
+        let merged = &a | &b;
+        assert_eq!(keys(&merged), vec![1, 3, 4, 2]);
+        assert_eq!(merged[&1], "A");
+
+        a |= OrderedHashMap::from([(3, "C"), (5, "E")]);
+        a |= &b;
+        assert_eq!(
+            a,
+            OrderedHashMap::from(vec![(1, "A"), (3, "C"), (5, "E"), (4, "D"), (2, "B")])
+        );
+        a.retain(|k, _| k % 2 == 1);
+        assert_eq!(keys(&a), vec![1, 3, 5]);
+        assert_consistent(&a);
+    }
 }
@@ -0,0 +1,752 @@
+//! The ordered storage behind `OrderedHashMap`.
+//!
+//! Entries are kept in a `VecDeque` in order, and a `RawTable` maps the hash of each key to
+//! the *stamp* of its entry. A stamp is the entry's slot in the deque plus `offset`, so popping
+//! from the front of the deque only needs to bump `offset` instead of rewriting the table.
+//!
+//! Removing an entry leaves a vacant slot (`None`) behind rather than shifting everything after
+//! it, which keeps removal O(1). Vacant slots at either end are trimmed right away, and the
+//! interior ones are compacted once they outnumber the occupied slots, so the amortized cost of
+//! every operation stays O(1).
+//!
+//! Positions and slots are the same while there are no vacant slots. Once there are, `Ranks`
+//! counts the occupied slots so that converting between the two is O(log n), at the price of
+//! an O(log n) update whenever a slot is filled or vacated.
+
+use super::ranks::Ranks;
+use hashbrown::raw::RawTable;
+use std::borrow::Borrow;
+use std::collections::vec_deque::{self, VecDeque};
+use std::ops::{Bound, Range, RangeBounds};
+
+#[derive(Clone)]
+pub(crate) struct Bucket<K, V> {
+    pub(crate) hash: u64,
+    pub(crate) key: K,
+    pub(crate) value: V,
+}
+
+#[derive(Clone)]
+pub(crate) struct OrderedCore<K, V> {
+    indices: RawTable<usize>,
+    entries: VecDeque<Option<Bucket<K, V>>>,
+    offset: usize,
+    len: usize,
+    ranks: Ranks,
+    ranked: bool,
+}
+
+#[inline]
+fn hash_of<K, V>(entries: &VecDeque<Option<Bucket<K, V>>>, offset: usize, stamp: usize) -> u64 {
+    match entries[stamp.wrapping_sub(offset)] {
+        Some(ref bucket) => bucket.hash,
+        None => unreachable!("index table points to a vacant slot"),
+    }
+}
+
+impl<K, V> OrderedCore<K, V> {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        OrderedCore {
+            indices: RawTable::new(),
+            entries: VecDeque::new(),
+            offset: 0,
+            len: 0,
+            ranks: Ranks::default(),
+            ranked: true,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        OrderedCore {
+            indices: RawTable::with_capacity(capacity),
+            entries: VecDeque::with_capacity(capacity),
+            offset: 0,
+            len: 0,
+            ranks: Ranks::default(),
+            ranked: true,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        usize::min(self.indices.capacity(), self.entries.capacity())
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.indices.clear();
+        self.entries.clear();
+        self.offset = 0;
+        self.len = 0;
+        self.ranks.clear();
+    }
+
+    /// Sets whether positions are tracked with `Ranks` while there are vacant slots. Without
+    /// them, converting between positions and slots walks the deque.
+    pub(crate) fn set_ranked(&mut self, ranked: bool) {
+        self.ranked = ranked;
+        if ranked {
+            self.track_gaps();
+        } else {
+            self.ranks.clear();
+        }
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        let entries = &self.entries;
+        let offset = self.offset;
+        self.indices
+            .reserve(additional, |&stamp| hash_of(entries, offset, stamp));
+        self.entries.reserve(additional);
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.compact();
+        let entries = &self.entries;
+        let offset = self.offset;
+        self.indices
+            .shrink_to(0, |&stamp| hash_of(entries, offset, stamp));
+        self.entries.shrink_to_fit();
+    }
+
+    /// All slots, vacant ones included.
+    #[inline]
+    pub(crate) fn slots(&self) -> &VecDeque<Option<Bucket<K, V>>> {
+        &self.entries
+    }
+
+    #[inline]
+    pub(crate) fn slots_mut(&mut self) -> &mut VecDeque<Option<Bucket<K, V>>> {
+        &mut self.entries
+    }
+
+    #[inline]
+    pub(crate) fn into_slots(self) -> VecDeque<Option<Bucket<K, V>>> {
+        self.entries
+    }
+
+    #[inline]
+    pub(crate) fn bucket(&self, slot: usize) -> &Bucket<K, V> {
+        match self.entries[slot] {
+            Some(ref bucket) => bucket,
+            None => unreachable!("slot {} is vacant", slot),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn bucket_mut(&mut self, slot: usize) -> &mut Bucket<K, V> {
+        match self.entries[slot] {
+            Some(ref mut bucket) => bucket,
+            None => unreachable!("slot {} is vacant", slot),
+        }
+    }
+
+    /// Returns the slot of the entry at `position` in the order.
+    ///
+    /// This is O(1) while there are no vacant slots, O(log n) with ranks and walks the deque
+    /// otherwise.
+    pub(crate) fn slot_of(&self, position: usize) -> Option<usize> {
+        if position >= self.len {
+            None
+        } else if self.entries.len() == self.len {
+            Some(position)
+        } else if self.ranks.is_built() {
+            Some(self.ranks.select(self.offset, position))
+        } else {
+            self.entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.is_some())
+                .nth(position)
+                .map(|(slot, _)| slot)
+        }
+    }
+
+    /// Returns the position in the order of the entry in `slot`.
+    ///
+    /// This is O(1) while there are no vacant slots, O(log n) with ranks and walks the deque
+    /// otherwise.
+    pub(crate) fn position_of(&self, slot: usize) -> usize {
+        if self.entries.len() == self.len {
+            slot
+        } else if self.ranks.is_built() {
+            self.ranks.rank(self.offset, slot)
+        } else {
+            self.entries
+                .range(..slot)
+                .filter(|entry| entry.is_some())
+                .count()
+        }
+    }
+
+    /// Returns the slot of the entry with the given hash and key.
+    pub(crate) fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let entries = &self.entries;
+        let offset = self.offset;
+        self.indices
+            .find(hash, |&stamp| match entries[stamp.wrapping_sub(offset)] {
+                Some(ref bucket) => bucket.key.borrow() == key,
+                None => false,
+            })
+            .map(|bucket| unsafe { *bucket.as_ref() }.wrapping_sub(offset))
+    }
+
+    /// Appends an entry, which must not already be present, and returns its slot.
+    pub(crate) fn push(&mut self, hash: u64, key: K, value: V) -> usize {
+        let slot = self.entries.len();
+        let stamp = slot.wrapping_add(self.offset);
+        let entries = &self.entries;
+        let offset = self.offset;
+        self.indices
+            .insert(hash, stamp, |&stamp| hash_of(entries, offset, stamp));
+        self.entries.push_back(Some(Bucket { hash, key, value }));
+        self.len += 1;
+        self.occupy(slot);
+        slot
+    }
+
+    /// Inserts an entry, which must not already be present, at `position` in the order and
+    /// returns its slot.
+    ///
+    /// Slots handed out before the call are invalidated.
+    pub(crate) fn insert_at(&mut self, position: usize, hash: u64, key: K, value: V) -> usize {
+        assert!(
+            position <= self.len,
+            "insertion index {} is out of bounds for length {}",
+            position,
+            self.len
+        );
+        self.compact();
+        let slot = self.push(hash, key, value);
+        self.move_slot(slot, position);
+        position
+    }
+
+    /// Removes the entry in `slot`, keeping the order of the remaining entries.
+    ///
+    /// Slots handed out before the call are invalidated.
+    pub(crate) fn remove(&mut self, slot: usize) -> Bucket<K, V> {
+        let bucket = self.entries[slot].take().expect("slot is vacant");
+        self.erase_index(bucket.hash, slot);
+        self.vacate(slot);
+        self.len -= 1;
+        self.tidy();
+        bucket
+    }
+
+    /// Removes the entry in `slot` by moving the last entry into its place.
+    ///
+    /// Slots handed out before the call are invalidated.
+    pub(crate) fn swap_remove(&mut self, slot: usize) -> Bucket<K, V> {
+        let last = self.entries.len() - 1;
+        if slot == last {
+            return self.remove(slot);
+        }
+        let bucket = self.entries[slot].take().expect("slot is vacant");
+        self.erase_index(bucket.hash, slot);
+        let moved = match self.entries.pop_back() {
+            Some(Some(moved)) => moved,
+            _ => unreachable!("the back of the deque is vacant"),
+        };
+        self.restamp(moved.hash, last, slot);
+        self.entries[slot] = Some(moved);
+        self.vacate(last);
+        self.len -= 1;
+        self.tidy();
+        bucket
+    }
+
+    /// Moves the entry in `slot` to the back of the order and returns its new slot.
+    ///
+    /// Slots handed out before the call are invalidated.
+    pub(crate) fn move_to_back(&mut self, slot: usize) -> usize {
+        if slot + 1 == self.entries.len() {
+            return slot;
+        }
+        let bucket = self.entries[slot].take().expect("slot is vacant");
+        let new_slot = self.entries.len();
+        self.restamp(bucket.hash, slot, new_slot);
+        self.entries.push_back(Some(bucket));
+        self.vacate(slot);
+        self.occupy(new_slot);
+        self.tidy();
+        self.entries.len() - 1
+    }
+
+    /// Moves the entry in `slot` to the front of the order and returns its new slot, which is
+    /// always 0.
+    ///
+    /// Slots handed out before the call are invalidated.
+    pub(crate) fn move_to_front(&mut self, slot: usize) -> usize {
+        if slot == 0 {
+            return slot;
+        }
+        let bucket = self.entries[slot].take().expect("slot is vacant");
+        self.vacate(slot);
+        let old_stamp = slot.wrapping_add(self.offset);
+        self.offset = self.offset.wrapping_sub(1);
+        self.restamp(bucket.hash, old_stamp.wrapping_sub(self.offset), 0);
+        self.entries.push_front(Some(bucket));
+        self.occupy(0);
+        self.tidy();
+        0
+    }
+
+    /// Swaps the entries in two occupied slots.
+    pub(crate) fn swap_slots(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        let (stamp_a, stamp_b) = (a.wrapping_add(self.offset), b.wrapping_add(self.offset));
+        let index_a = self
+            .indices
+            .find(self.bucket(a).hash, |&s| s == stamp_a)
+            .expect("index table out of sync");
+        let index_b = self
+            .indices
+            .find(self.bucket(b).hash, |&s| s == stamp_b)
+            .expect("index table out of sync");
+        unsafe {
+            *index_a.as_mut() = stamp_b;
+            *index_b.as_mut() = stamp_a;
+        }
+        self.entries.swap(a, b);
+    }
+
+    /// Moves the entry in slot `from` to slot `to`, shifting the entries in between.
+    ///
+    /// The deque must not have vacant slots.
+    pub(crate) fn move_slot(&mut self, from: usize, to: usize) {
+        debug_assert_eq!(self.entries.len(), self.len);
+        if from == to {
+            return;
+        }
+        let offset = self.offset;
+        unsafe {
+            for index in self.indices.iter() {
+                let stamp = index.as_mut();
+                let slot = stamp.wrapping_sub(offset);
+                let new_slot = if slot == from {
+                    to
+                } else if from < to && from < slot && slot <= to {
+                    slot - 1
+                } else if to < from && to <= slot && slot < from {
+                    slot + 1
+                } else {
+                    slot
+                };
+                *stamp = new_slot.wrapping_add(offset);
+            }
+        }
+        let entry = self.entries.remove(from).expect("slot out of bounds");
+        self.entries.insert(to, entry);
+    }
+
+    /// Points the index of the entry with `hash` in `old_slot` at `new_slot`.
+    fn restamp(&mut self, hash: u64, old_slot: usize, new_slot: usize) {
+        let old_stamp = old_slot.wrapping_add(self.offset);
+        let index = self
+            .indices
+            .find(hash, |&s| s == old_stamp)
+            .expect("index table out of sync");
+        unsafe { *index.as_mut() = new_slot.wrapping_add(self.offset) };
+    }
+
+    fn erase_index(&mut self, hash: u64, slot: usize) {
+        let stamp = slot.wrapping_add(self.offset);
+        let index = self
+            .indices
+            .find(hash, |&s| s == stamp)
+            .expect("index table out of sync");
+        unsafe { self.indices.erase_no_drop(&index) };
+    }
+
+    /// Keeps only the entries for which `keep` returns `true`, preserving their order.
+    pub(crate) fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for slot in 0..self.entries.len() {
+            let remove = match self.entries[slot] {
+                Some(ref mut bucket) => !keep(&bucket.key, &mut bucket.value),
+                None => false,
+            };
+            if remove {
+                let hash = self.bucket(slot).hash;
+                self.erase_index(hash, slot);
+                self.entries[slot] = None;
+                self.vacate(slot);
+                self.len -= 1;
+            }
+        }
+        self.tidy();
+    }
+
+    /// Removes the entries at `range`, counted in positions, and returns them as slots along
+    /// with how many of those slots are occupied.
+    ///
+    /// An empty range touches nothing. A range starting at the front only erases its own
+    /// entries and advances `offset`; any other range compacts the deque and renumbers the
+    /// entries after it.
+    pub(crate) fn drain<R>(
+        &mut self,
+        range: R,
+    ) -> (vec_deque::Drain<'_, Option<Bucket<K, V>>>, usize)
+    where
+        R: RangeBounds<usize>,
+    {
+        let Range { start, end } = simplify_range(range, self.len);
+        let count = end - start;
+        if count == 0 {
+            return (self.entries.drain(0..0), 0);
+        }
+        if start == 0 {
+            if self.entries.len() - self.len > self.len - count {
+                self.compact();
+            }
+            let last = match self.slot_of(end) {
+                Some(slot) => slot,
+                None => self.entries.len(),
+            };
+            for slot in 0..last {
+                let hash = match self.entries[slot] {
+                    Some(ref bucket) => bucket.hash,
+                    None => continue,
+                };
+                self.erase_index(hash, slot);
+                self.vacate(slot);
+            }
+            self.offset = self.offset.wrapping_add(last);
+            self.len -= count;
+            return (self.entries.drain(..last), count);
+        }
+        self.compact();
+        for slot in start..end {
+            let hash = self.bucket(slot).hash;
+            self.erase_index(hash, slot);
+        }
+        if end < self.len {
+            let shift = end - start;
+            let offset = self.offset;
+            unsafe {
+                for index in self.indices.iter() {
+                    let stamp = index.as_mut();
+                    if stamp.wrapping_sub(offset) >= end {
+                        *stamp = stamp.wrapping_sub(shift);
+                    }
+                }
+            }
+        }
+        self.len -= count;
+        (self.entries.drain(start..end), count)
+    }
+
+    /// Trims the deque after entries were removed or moved, compacts it once vacant slots
+    /// outnumber the entries, and starts tracking ranks if vacant slots remain.
+    fn tidy(&mut self) {
+        self.trim();
+        if self.entries.len() - self.len > self.len {
+            self.compact();
+        }
+        self.track_gaps();
+    }
+
+    /// Drops vacant slots from both ends of the deque.
+    fn trim(&mut self) {
+        while let Some(None) = self.entries.back() {
+            self.entries.pop_back();
+        }
+        while let Some(None) = self.entries.front() {
+            self.entries.pop_front();
+            self.offset = self.offset.wrapping_add(1);
+        }
+    }
+
+    /// Removes every vacant slot and renumbers the index table.
+    pub(crate) fn compact(&mut self) {
+        if self.entries.len() == self.len {
+            return;
+        }
+        self.entries.retain(Option::is_some);
+        self.offset = 0;
+        self.ranks.clear();
+        self.rebuild_indices();
+    }
+
+    /// Builds the ranks if this core keeps them and there are vacant slots.
+    fn track_gaps(&mut self) {
+        if self.ranked && !self.ranks.is_built() && self.entries.len() != self.len {
+            self.rebuild_ranks();
+        }
+    }
+
+    fn rebuild_ranks(&mut self) {
+        let occupied = self.entries.iter().map(Option::is_some);
+        self.ranks = Ranks::build(self.offset, self.entries.len(), occupied);
+    }
+
+    /// Counts `slot` as occupied in the ranks, if they are built.
+    fn occupy(&mut self, slot: usize) {
+        if !self.ranks.is_built() {
+            return;
+        }
+        if self.ranks.fits(self.entries.len()) {
+            self.ranks.occupy(slot.wrapping_add(self.offset));
+        } else {
+            self.rebuild_ranks();
+        }
+    }
+
+    /// Counts `slot` as vacant in the ranks, if they are built.
+    fn vacate(&mut self, slot: usize) {
+        if self.ranks.is_built() {
+            self.ranks.vacate(slot.wrapping_add(self.offset));
+        }
+    }
+
+    fn rebuild_indices(&mut self) {
+        self.indices.clear();
+        let entries = &self.entries;
+        let offset = self.offset;
+        for (slot, entry) in entries.iter().enumerate() {
+            if let Some(ref bucket) = *entry {
+                self.indices
+                    .insert(bucket.hash, slot.wrapping_add(offset), |&stamp| {
+                        hash_of(entries, offset, stamp)
+                    });
+            }
+        }
+    }
+}
+
+/// Turns `range` into a concrete `Range`, panicking if it does not fit in `len`.
+pub(crate) fn simplify_range<R>(range: R, len: usize) -> Range<usize>
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => i.checked_add(1).expect("range start overflow"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&i) => i.checked_add(1).expect("range end overflow"),
+        Bound::Excluded(&i) => i,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "range start {} is after end {}", start, end);
+    assert!(
+        end <= len,
+        "range end {} is out of bounds for length {}",
+        end,
+        len
+    );
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderedCore;
+
+    fn keys(core: &OrderedCore<u64, u64>) -> Vec<u64> {
+        core.slots().iter().flatten().map(|b| b.key).collect()
+    }
+
+    #[test]
+    fn vacant_slots_are_trimmed_and_compacted() {
+        let mut core = OrderedCore::new();
+        for i in 0..10 {
+            core.push(i % 3, i, i);
+        }
+        let slot = core.find(0, &0).unwrap();
+        core.remove(slot);
+        assert_eq!(core.slots().len(), 9);
+        let slot = core.find(9 % 3, &9).unwrap();
+        core.remove(slot);
+        assert_eq!(core.slots().len(), 8);
+
+        for i in (2..8).step_by(2) {
+            let slot = core.find(i % 3, &i).unwrap();
+            core.remove(slot);
+        }
+        assert_eq!(keys(&core), vec![1, 3, 5, 7, 8]);
+        assert_eq!(core.len(), 5);
+        assert!(core.slots().len() - core.len() <= core.len());
+
+        for i in [1, 3, 5, 7, 8].iter() {
+            let slot = core.find(i % 3, i).unwrap();
+            assert_eq!(core.bucket(slot).value, *i);
+        }
+        assert_eq!(core.find(2, &2), None);
+    }
+
+    #[test]
+    fn moves_keep_stamps_valid() {
+        let mut core = OrderedCore::new();
+        for i in 0..6 {
+            core.push(i, i, i);
+        }
+        core.move_to_back(0);
+        core.move_to_front(core.find(3, &3).unwrap());
+        core.move_to_front(core.find(5, &5).unwrap());
+        core.move_to_back(core.find(5, &5).unwrap());
+        assert_eq!(keys(&core), vec![3, 1, 2, 4, 0, 5]);
+        for i in 0..6 {
+            let slot = core.find(i, &i).unwrap();
+            assert_eq!(core.bucket(slot).key, i);
+        }
+        assert!(core.slots().len() - core.len() <= core.len());
+    }
+
+    #[test]
+    fn positions_skip_vacant_slots() {
+        let mut core = OrderedCore::new();
+        for i in 0..6 {
+            core.push(i, i, i);
+        }
+        core.remove(core.find(2, &2).unwrap());
+        assert_eq!(core.slot_of(2), Some(3));
+        assert_eq!(core.position_of(3), 2);
+        assert_eq!(core.slot_of(5), None);
+
+        core.compact();
+        core.swap_slots(0, 4);
+        core.move_slot(1, 3);
+        assert_eq!(keys(&core), vec![5, 3, 4, 1, 0]);
+        for (slot, i) in [5, 3, 4, 1, 0].iter().enumerate() {
+            assert_eq!(core.find(*i, i), Some(slot));
+        }
+    }
+
+    #[test]
+    fn swap_remove_fills_the_hole() {
+        let mut core = OrderedCore::new();
+        for i in 0..6 {
+            core.push(i, i, i);
+        }
+        core.remove(core.find(4, &4).unwrap());
+        assert_eq!(core.swap_remove(core.find(1, &1).unwrap()).key, 1);
+        assert_eq!(keys(&core), vec![0, 5, 2, 3]);
+        assert_eq!(core.swap_remove(core.find(3, &3).unwrap()).key, 3);
+        assert_eq!(keys(&core), vec![0, 5, 2]);
+        for i in [0, 5, 2].iter() {
+            assert_eq!(core.bucket(core.find(*i, i).unwrap()).key, *i);
+        }
+    }
+
+    #[test]
+    fn drain_renumbers_following_entries() {
+        let mut core = OrderedCore::new();
+        for i in 0..10 {
+            core.push(i, i, i);
+        }
+        let slot = core.find(4, &4).unwrap();
+        core.remove(slot);
+        let (slots, count) = core.drain(2..5);
+        let drained: Vec<_> = slots.flatten().map(|b| b.key).collect();
+        assert_eq!(drained, vec![2, 3, 5]);
+        assert_eq!(count, 3);
+        assert_eq!(keys(&core), vec![0, 1, 6, 7, 8, 9]);
+        for (slot, i) in [0, 1, 6, 7, 8, 9].iter().enumerate() {
+            assert_eq!(core.find(*i, i), Some(slot));
+        }
+        assert_eq!(core.find(5, &5), None);
+    }
+
+    #[test]
+    fn prefix_drain_only_advances_the_offset() {
+        let mut core = OrderedCore::new();
+        for i in 0..10 {
+            core.push(i, i, i);
+        }
+        core.remove(core.find(2, &2).unwrap());
+        core.remove(core.find(6, &6).unwrap());
+        let (slots, count) = core.drain(0..4);
+        let drained: Vec<_> = slots.flatten().map(|b| b.key).collect();
+        assert_eq!(drained, vec![0, 1, 3, 4]);
+        assert_eq!(count, 4);
+        assert_eq!(keys(&core), vec![5, 7, 8, 9]);
+        assert_eq!(core.slot_of(1), Some(2));
+        for i in [5, 7, 8, 9].iter() {
+            assert_eq!(core.bucket(core.find(*i, i).unwrap()).key, *i);
+        }
+
+        assert_eq!(core.drain(2..2).1, 0);
+        assert_eq!(core.slots().len(), 5);
+        assert_eq!(core.drain(..).1, 4);
+        assert_eq!(core.len(), 0);
+        assert!(core.slots().is_empty());
+    }
+
+    fn assert_ranks_match_slots(core: &OrderedCore<u64, u64>) {
+        let occupied = core.slots().iter().enumerate().filter(|(_, e)| e.is_some());
+        for (position, (slot, _)) in occupied.enumerate() {
+            assert_eq!(core.slot_of(position), Some(slot));
+            assert_eq!(core.position_of(slot), position);
+        }
+        assert_eq!(core.slot_of(core.len()), None);
+    }
+
+    #[test]
+    fn ranks_follow_moves_and_removals() {
+        let mut core = OrderedCore::new();
+        for i in 0..64 {
+            core.push(i, i, i);
+        }
+        for step in 0..400 {
+            let key = (step * 37 % 64) as u64;
+            match (core.find(key, &key), step % 6) {
+                (Some(slot), 0) => {
+                    core.remove(slot);
+                }
+                (Some(slot), 1) => {
+                    core.move_to_back(slot);
+                }
+                (Some(slot), 2) => {
+                    core.move_to_front(slot);
+                }
+                (Some(slot), 3) => {
+                    core.swap_remove(slot);
+                }
+                (Some(_), 4) if core.len() > 2 => {
+                    core.drain(..2);
+                }
+                (None, _) => {
+                    core.push(key, key, key);
+                }
+                _ => core.retain(|&k, _| k != key),
+            }
+            assert!(core.ranks.is_built() || core.slots().len() == core.len());
+            assert_ranks_match_slots(&core);
+        }
+    }
+
+    #[test]
+    fn front_removal_keeps_stamps_valid() {
+        let mut core = OrderedCore::new();
+        for i in 0..100 {
+            core.push(i, i, i);
+            if i % 2 == 1 {
+                let slot = core.find(i / 2, &(i / 2)).unwrap();
+                assert_eq!(slot, 0);
+                core.remove(slot);
+            }
+        }
+        assert_eq!(core.len(), 50);
+        assert_eq!(keys(&core), (50..100).collect::<Vec<_>>());
+        for i in 50..100 {
+            assert_eq!(core.find(i, &i), Some((i - 50) as usize));
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! The entry API of `OrderedHashMap`.
+
+use super::core::OrderedCore;
+use std::mem;
+
+/// A view into a single entry in a map, which may either be vacant or occupied.
+///
+/// This `enum` is constructed from the [`entry`] method on [`OrderedHashMap`].
+///
+/// [`entry`]: struct.OrderedHashMap.html#method.entry
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub enum Entry<'a, K, V> {
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// A vacant entry.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// A view into an occupied entry in an `OrderedHashMap`.
+/// It is part of the [`Entry`] enum.
+///
+/// [`Entry`]: enum.Entry.html
+pub struct OccupiedEntry<'a, K, V> {
+    pub(super) core: &'a mut OrderedCore<K, V>,
+    pub(super) slot: usize,
+}
+
+/// A view into a vacant entry in an `OrderedHashMap`.
+/// It is part of the [`Entry`] enum.
+///
+/// [`Entry`]: enum.Entry.html
+pub struct VacantEntry<'a, K, V> {
+    pub(super) core: &'a mut OrderedCore<K, V>,
+    pub(super) hash: u64,
+    pub(super) key: K,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default if empty, and returns
+    /// a mutable reference to the value in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    ///
+    /// map.entry("poneyland").or_insert(3);
+    /// assert_eq!(map[&"poneyland"], 3);
+    ///
+    /// *map.entry("poneyland").or_insert(10) *= 2;
+    /// assert_eq!(map[&"poneyland"], 6);
+    /// ```
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the default function if empty,
+    /// and returns a mutable reference to the value in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<&str, String> = OrderedHashMap::new();
+    /// let s = "hoho".to_string();
+    ///
+    /// map.entry("poneyland").or_insert_with(|| s);
+    /// assert_eq!(map[&"poneyland"], "hoho".to_string());
+    /// ```
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    /// assert_eq!(map.entry("poneyland").key(), &"poneyland");
+    /// ```
+    #[inline]
+    pub fn key(&self) -> &K {
+        match *self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key(),
+        }
+    }
+
+    /// Returns the position of this entry in the order: where it is if occupied, or where it
+    /// would be appended if vacant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// assert_eq!(map.entry("a").index(), 0);
+    /// assert_eq!(map.entry("z").index(), 2);
+    /// ```
+    #[inline]
+    pub fn index(&self) -> usize {
+        match *self {
+            Entry::Occupied(ref entry) => entry.index(),
+            Entry::Vacant(ref entry) => entry.index(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    ///
+    /// map.entry("poneyland")
+    ///    .and_modify(|e| { *e += 1 })
+    ///    .or_insert(42);
+    /// assert_eq!(map[&"poneyland"], 42);
+    ///
+    /// map.entry("poneyland")
+    ///    .and_modify(|e| { *e += 1 })
+    ///    .or_insert(42);
+    /// assert_eq!(map[&"poneyland"], 43);
+    /// ```
+    #[inline]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty,
+    /// and returns a mutable reference to the value in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    ///
+    /// let mut map: OrderedHashMap<&str, Option<u32>> = OrderedHashMap::new();
+    /// map.entry("poneyland").or_default();
+    ///
+    /// assert_eq!(map[&"poneyland"], None);
+    /// ```
+    #[inline]
+    pub fn or_default(self) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Default::default()),
+        }
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Gets a reference to the key in the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.core.bucket(self.slot).key
+    }
+
+    /// Returns the position of the entry in the order.
+    ///
+    /// This is O(1) unless entries have been removed from the middle of the map since it was
+    /// last compacted, in which case it walks the map.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.core.position_of(self.slot)
+    }
+
+    /// Gets a reference to the value in the entry.
+    #[inline]
+    pub fn get(&self) -> &V {
+        &self.core.bucket(self.slot).value
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    ///
+    /// If you need a reference to the `OccupiedEntry` which may outlive the
+    /// destruction of the `Entry` value, see [`into_mut`].
+    ///
+    /// [`into_mut`]: #method.into_mut
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.core.bucket_mut(self.slot).value
+    }
+
+    /// Converts the entry into a mutable reference to its value, with a lifetime bound to the
+    /// map itself.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.core.bucket_mut(self.slot).value
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value. The entry keeps its
+    /// position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    /// use ordered::map::Entry;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    /// map.entry("poneyland").or_insert(12);
+    ///
+    /// if let Entry::Occupied(mut o) = map.entry("poneyland") {
+    ///     assert_eq!(o.insert(15), 12);
+    /// }
+    /// assert_eq!(map[&"poneyland"], 15);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Moves the entry to the end of the order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    /// use ordered::map::Entry;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    ///
+    /// if let Entry::Occupied(mut o) = map.entry("a") {
+    ///     o.move_to_end();
+    ///     assert_eq!(o.index(), 1);
+    /// }
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"b", &"a"]);
+    /// ```
+    #[inline]
+    pub fn move_to_end(&mut self) {
+        self.slot = self.core.move_to_back(self.slot);
+    }
+
+    /// Moves the entry to the front of the order.
+    #[inline]
+    pub fn move_to_front(&mut self) {
+        self.slot = self.core.move_to_front(self.slot);
+    }
+
+    /// Takes the value out of the entry, and returns it. The remaining entries keep their
+    /// relative order.
+    #[inline]
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Takes the ownership of the key and value from the map. The remaining entries keep their
+    /// relative order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    /// use ordered::map::Entry;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    /// map.entry("poneyland").or_insert(12);
+    ///
+    /// if let Entry::Occupied(o) = map.entry("poneyland") {
+    ///     assert_eq!(o.remove_entry(), ("poneyland", 12));
+    /// }
+    /// assert_eq!(map.contains_key("poneyland"), false);
+    /// ```
+    #[inline]
+    pub fn remove_entry(self) -> (K, V) {
+        let bucket = self.core.remove(self.slot);
+        (bucket.key, bucket.value)
+    }
+
+    /// Takes the ownership of the key and value from the map, moving the last entry of the map
+    /// into its place. This is O(1) but disturbs the order.
+    #[inline]
+    pub fn swap_remove_entry(self) -> (K, V) {
+        let bucket = self.core.swap_remove(self.slot);
+        (bucket.key, bucket.value)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Gets a reference to the key that would be used when inserting a value
+    /// through the `VacantEntry`.
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take ownership of the key.
+    #[inline]
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Returns the position the entry would be appended at, which is the length of the map.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Sets the value of the entry, appending it to the end of the order, and returns a
+    /// mutable reference to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    /// use ordered::map::Entry;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    ///
+    /// if let Entry::Vacant(o) = map.entry("poneyland") {
+    ///     o.insert(37);
+    /// }
+    /// assert_eq!(map[&"poneyland"], 37);
+    /// ```
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V {
+        let slot = self.core.push(self.hash, self.key, value);
+        &mut self.core.bucket_mut(slot).value
+    }
+
+    /// Sets the value of the entry, inserting it at `index` in the order, and returns a mutable
+    /// reference to it. The entries from `index` onwards shift back by one.
+    ///
+    /// This is O(n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashMap;
+    /// use ordered::map::Entry;
+    ///
+    /// let mut map: OrderedHashMap<&str, u32> = OrderedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("c", 3);
+    ///
+    /// if let Entry::Vacant(o) = map.entry("b") {
+    ///     o.insert_at(1, 2);
+    /// }
+    /// let keys: Vec<_> = map.keys().collect();
+    /// assert_eq!(keys, [&"a", &"b", &"c"]);
+    /// ```
+    #[inline]
+    pub fn insert_at(self, index: usize, value: V) -> &'a mut V {
+        let slot = self.core.insert_at(index, self.hash, self.key, value);
+        &mut self.core.bucket_mut(slot).value
+    }
+}
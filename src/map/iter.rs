@@ -0,0 +1,206 @@
+//! Iterators over an `OrderedHashMap`, all of which yield entries in order.
+
+use super::core::Bucket;
+use std::collections::vec_deque;
+use std::iter::FusedIterator;
+
+/// Implements the iterator traits for a type that walks a deque of slots, skipping the vacant
+/// ones. `remaining` counts the occupied slots left, which makes the iterator exact-size.
+macro_rules! slot_iterator {
+    ([$($gen:tt)*] $name:ty, $item:ty, $bucket:pat => $map:expr) => {
+        impl<$($gen)*> Iterator for $name {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                for slot in self.slots.by_ref() {
+                    if let Some($bucket) = slot {
+                        self.remaining -= 1;
+                        return Some($map);
+                    }
+                }
+                None
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.remaining, Some(self.remaining))
+            }
+        }
+
+        impl<$($gen)*> DoubleEndedIterator for $name {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                while let Some(slot) = self.slots.next_back() {
+                    if let Some($bucket) = slot {
+                        self.remaining -= 1;
+                        return Some($map);
+                    }
+                }
+                None
+            }
+        }
+
+        impl<$($gen)*> ExactSizeIterator for $name {
+            #[inline]
+            fn len(&self) -> usize {
+                self.remaining
+            }
+        }
+
+        impl<$($gen)*> FusedIterator for $name {}
+    };
+}
+
+/// Implements the iterator traits for a type that projects the items of the iterator in its
+/// `inner` field.
+macro_rules! projected_iterator {
+    ([$($gen:tt)*] $name:ty, $item:ty, $pat:pat => $map:expr) => {
+        impl<$($gen)*> Iterator for $name {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next().map(|$pat| $map)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+        }
+
+        impl<$($gen)*> DoubleEndedIterator for $name {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.inner.next_back().map(|$pat| $map)
+            }
+        }
+
+        impl<$($gen)*> ExactSizeIterator for $name {
+            #[inline]
+            fn len(&self) -> usize {
+                self.inner.len()
+            }
+        }
+
+        impl<$($gen)*> FusedIterator for $name {}
+    };
+}
+
+/// An iterator over the entries of an `OrderedHashMap`.
+///
+/// This `struct` is created by the [`iter`] method on [`OrderedHashMap`].
+///
+/// [`iter`]: struct.OrderedHashMap.html#method.iter
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub struct Iter<'a, K, V> {
+    pub(super) slots: vec_deque::Iter<'a, Option<Bucket<K, V>>>,
+    pub(super) remaining: usize,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            slots: self.slots.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+slot_iterator!(['a, K, V] Iter<'a, K, V>, (&'a K, &'a V), bucket => (&bucket.key, &bucket.value));
+
+/// A mutable iterator over the entries of an `OrderedHashMap`.
+///
+/// This `struct` is created by the [`iter_mut`] method on [`OrderedHashMap`].
+///
+/// [`iter_mut`]: struct.OrderedHashMap.html#method.iter_mut
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub struct IterMut<'a, K, V> {
+    pub(super) slots: vec_deque::IterMut<'a, Option<Bucket<K, V>>>,
+    pub(super) remaining: usize,
+}
+
+slot_iterator!(['a, K, V] IterMut<'a, K, V>, (&'a K, &'a mut V), bucket => (&bucket.key, &mut bucket.value));
+
+/// An owning iterator over the entries of an `OrderedHashMap`.
+///
+/// This `struct` is created by the [`into_iter`] method on [`OrderedHashMap`]
+/// (provided by the `IntoIterator` trait).
+///
+/// [`into_iter`]: struct.OrderedHashMap.html#method.into_iter
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub struct IntoIter<K, V> {
+    pub(super) slots: vec_deque::IntoIter<Option<Bucket<K, V>>>,
+    pub(super) remaining: usize,
+}
+
+slot_iterator!([K, V] IntoIter<K, V>, (K, V), bucket => (bucket.key, bucket.value));
+
+/// A draining iterator over a range of entries of an `OrderedHashMap`.
+///
+/// This `struct` is created by the [`drain`] method on [`OrderedHashMap`].
+///
+/// [`drain`]: struct.OrderedHashMap.html#method.drain
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub struct Drain<'a, K, V> {
+    pub(super) slots: vec_deque::Drain<'a, Option<Bucket<K, V>>>,
+    pub(super) remaining: usize,
+}
+
+slot_iterator!(['a, K, V] Drain<'a, K, V>, (K, V), bucket => (bucket.key, bucket.value));
+
+/// An iterator over the keys of an `OrderedHashMap`.
+///
+/// This `struct` is created by the [`keys`] method on [`OrderedHashMap`].
+///
+/// [`keys`]: struct.OrderedHashMap.html#method.keys
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub struct Keys<'a, K, V> {
+    pub(super) inner: Iter<'a, K, V>,
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Keys {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+projected_iterator!(['a, K, V] Keys<'a, K, V>, &'a K, (k, _) => k);
+
+/// An iterator over the values of an `OrderedHashMap`.
+///
+/// This `struct` is created by the [`values`] method on [`OrderedHashMap`].
+///
+/// [`values`]: struct.OrderedHashMap.html#method.values
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub struct Values<'a, K, V> {
+    pub(super) inner: Iter<'a, K, V>,
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Values {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+projected_iterator!(['a, K, V] Values<'a, K, V>, &'a V, (_, v) => v);
+
+/// A mutable iterator over the values of an `OrderedHashMap`.
+///
+/// This `struct` is created by the [`values_mut`] method on [`OrderedHashMap`].
+///
+/// [`values_mut`]: struct.OrderedHashMap.html#method.values_mut
+/// [`OrderedHashMap`]: struct.OrderedHashMap.html
+pub struct ValuesMut<'a, K, V> {
+    pub(super) inner: IterMut<'a, K, V>,
+}
+
+projected_iterator!(['a, K, V] ValuesMut<'a, K, V>, &'a mut V, (_, v) => v);
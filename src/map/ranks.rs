@@ -0,0 +1,169 @@
+//! Occupancy counts over the slots of an `OrderedCore`, which turn positions into slots and
+//! back in O(log n) while the deque has vacant slots.
+//!
+//! The counts live in a Fenwick tree indexed by stamp modulo its size. The size is a power of
+//! two no smaller than the deque, so the stamps in the deque never collide and the deque can
+//! grow or shrink at either end without moving any count.
+
+#[derive(Clone, Default)]
+pub(crate) struct Ranks {
+    /// The Fenwick tree, 1-based. Empty until the ranks are built.
+    tree: Vec<usize>,
+}
+
+impl Ranks {
+    /// Builds the ranks of a deque whose first slot has the stamp `first`, from whether each
+    /// of its `len` slots is occupied.
+    pub(crate) fn build<I>(first: usize, len: usize, occupied: I) -> Ranks
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let size = len.max(4).next_power_of_two() * 2;
+        let mask = size - 1;
+        let mut tree = vec![0; size + 1];
+        for (slot, occupied) in occupied.into_iter().enumerate() {
+            if occupied {
+                tree[(first.wrapping_add(slot) & mask) + 1] = 1;
+            }
+        }
+        for i in 1..size {
+            let parent = i + lowest_bit(i);
+            if parent <= size {
+                tree[parent] += tree[i];
+            }
+        }
+        Ranks { tree }
+    }
+
+    #[inline]
+    pub(crate) fn is_built(&self) -> bool {
+        !self.tree.is_empty()
+    }
+
+    /// Returns `true` if a deque of `len` slots fits in the tree.
+    #[inline]
+    pub(crate) fn fits(&self, len: usize) -> bool {
+        len < self.tree.len()
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.tree = Vec::new();
+    }
+
+    /// Counts the slot with `stamp` as occupied.
+    pub(crate) fn occupy(&mut self, stamp: usize) {
+        let size = self.size();
+        let mut i = (stamp & (size - 1)) + 1;
+        while i <= size {
+            self.tree[i] += 1;
+            i += lowest_bit(i);
+        }
+    }
+
+    /// Counts the slot with `stamp` as vacant.
+    pub(crate) fn vacate(&mut self, stamp: usize) {
+        let size = self.size();
+        let mut i = (stamp & (size - 1)) + 1;
+        while i <= size {
+            self.tree[i] -= 1;
+            i += lowest_bit(i);
+        }
+    }
+
+    /// Returns how many of the slots before `slot` are occupied, in a deque whose first slot
+    /// has the stamp `first`.
+    pub(crate) fn rank(&self, first: usize, slot: usize) -> usize {
+        let mask = self.size() - 1;
+        let head = first & mask;
+        let index = first.wrapping_add(slot) & mask;
+        if index >= head {
+            self.prefix(index) - self.prefix(head)
+        } else {
+            self.prefix(self.size()) - self.prefix(head) + self.prefix(index)
+        }
+    }
+
+    /// Returns the slot of the occupied slot with `rank` occupied slots before it, in a deque
+    /// whose first slot has the stamp `first`. There must be such a slot.
+    pub(crate) fn select(&self, first: usize, rank: usize) -> usize {
+        let mask = self.size() - 1;
+        let head = first & mask;
+        let before = self.prefix(head);
+        let after = self.prefix(self.size()) - before;
+        let index = if rank < after {
+            self.search(before + rank)
+        } else {
+            self.search(rank - after)
+        };
+        index.wrapping_sub(head) & mask
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    /// Returns how many of the indices before `index` are occupied.
+    fn prefix(&self, index: usize) -> usize {
+        let mut sum = 0;
+        let mut i = index;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= lowest_bit(i);
+        }
+        sum
+    }
+
+    /// Returns the index with exactly `rank` occupied indices before it.
+    fn search(&self, mut rank: usize) -> usize {
+        let size = self.size();
+        let mut index = 0;
+        let mut step = size;
+        while step > 0 {
+            if index + step <= size && self.tree[index + step] <= rank {
+                index += step;
+                rank -= self.tree[index];
+            }
+            step >>= 1;
+        }
+        index
+    }
+}
+
+#[inline]
+fn lowest_bit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ranks;
+
+    #[test]
+    fn rank_and_select_wrap_around_the_tree() {
+        let occupied = [
+            true, false, true, true, false, false, true, true, true, false, true,
+        ];
+        let first = usize::MAX - 3;
+        let mut ranks = Ranks::build(first, occupied.len(), occupied.iter().cloned());
+        assert!(ranks.fits(32) && !ranks.fits(33));
+
+        let slots: Vec<_> = (0..occupied.len()).filter(|&s| occupied[s]).collect();
+        for (rank, &slot) in slots.iter().enumerate() {
+            assert_eq!(ranks.rank(first, slot), rank);
+            assert_eq!(ranks.select(first, rank), slot);
+        }
+
+        ranks.vacate(first.wrapping_add(3));
+        ranks.occupy(first.wrapping_add(4));
+        assert_eq!(ranks.select(first, 2), 4);
+        assert_eq!(ranks.rank(first, 6), 3);
+
+        let first = first.wrapping_add(2);
+        ranks.vacate(first.wrapping_sub(2));
+        assert_eq!(ranks.select(first, 0), 0);
+        assert_eq!(ranks.rank(first, 8), 5);
+        assert_eq!(ranks.select(first, 5), 8);
+    }
+}
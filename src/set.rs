@@ -0,0 +1,1358 @@
+use crate::map::{self, OrderedHashMap};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::{Chain, FromIterator, FusedIterator};
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, RangeBounds, Sub,
+    SubAssign,
+};
+
+/// A hash set that remembers the order in which values were first inserted.
+///
+/// It is an [`OrderedHashMap`] with `()` values, so it shares the same ordered storage and
+/// costs: insertion, lookup and removal are amortized O(1) and the values can be addressed by
+/// position. The set algebra methods yield values in a deterministic order: those coming from
+/// the left operand first, in its order, then those from the right operand, in its order.
+///
+/// [`OrderedHashMap`]: ../map/struct.OrderedHashMap.html
+#[derive(Clone)]
+pub struct OrderedHashSet<T, S = RandomState> {
+    map: OrderedHashMap<T, (), S>,
+}
+
+impl<T> OrderedHashSet<T, RandomState> {
+    /// Creates an empty `OrderedHashSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// let set: OrderedHashSet<i32> = OrderedHashSet::new();
+    /// ```
+    #[inline]
+    pub fn new() -> OrderedHashSet<T, RandomState> {
+        Default::default()
+    }
+
+    /// Creates an empty `OrderedHashSet` with the specified capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// let set: OrderedHashSet<i32> = OrderedHashSet::with_capacity(10);
+    /// assert!(set.capacity() >= 10);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> OrderedHashSet<T, RandomState> {
+        OrderedHashSet::with_capacity_and_hasher(capacity, Default::default())
+    }
+}
+
+impl<T, S> OrderedHashSet<T, S> {
+    /// Creates an empty `OrderedHashSet` which will use the given hash builder to hash
+    /// values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let s = RandomState::new();
+    /// let mut set = OrderedHashSet::with_hasher(s);
+    /// set.insert(2);
+    /// ```
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> OrderedHashSet<T, S> {
+        OrderedHashSet {
+            map: OrderedHashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty `OrderedHashSet` with the specified capacity, using `hash_builder`
+    /// to hash the values.
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> OrderedHashSet<T, S> {
+        OrderedHashSet {
+            map: OrderedHashMap::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
+    /// Returns the number of elements the set can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Returns the number of elements in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut v = OrderedHashSet::new();
+    /// assert_eq!(v.len(), 0);
+    /// v.insert(1);
+    /// assert_eq!(v.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the set, removing all values.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// An iterator visiting all elements in insertion order.
+    /// The iterator element type is `&'a T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut set = OrderedHashSet::new();
+    /// set.insert("b");
+    /// set.insert("a");
+    ///
+    /// let values: Vec<_> = set.iter().collect();
+    /// assert_eq!(values, [&"b", &"a"]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.keys(),
+        }
+    }
+
+    /// Removes the values at the positions in `range` and returns them in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the end point is
+    /// greater than the length of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut set: OrderedHashSet<_> = (1..=4).collect();
+    /// let drained: Vec<_> = set.drain(..2).collect();
+    /// assert_eq!(drained, [1, 2]);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    #[inline]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        Drain {
+            iter: self.map.drain(range),
+        }
+    }
+
+    /// Returns the first value in the order.
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.map.front().map(|(k, _)| k)
+    }
+
+    /// Returns the last value in the order.
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.map.back().map(|(k, _)| k)
+    }
+
+    /// Removes and returns the first value in the order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut set: OrderedHashSet<_> = (1..=3).collect();
+    /// assert_eq!(set.pop_front(), Some(1));
+    /// assert_eq!(set.front(), Some(&2));
+    /// ```
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.map.pop_front().map(|(k, _)| k)
+    }
+
+    /// Removes and returns the last value in the order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut set: OrderedHashSet<_> = (1..=3).collect();
+    /// assert_eq!(set.pop_back(), Some(3));
+    /// assert_eq!(set.back(), Some(&2));
+    /// ```
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.map.pop_back().map(|(k, _)| k)
+    }
+
+    /// Returns the value at `index` in the order.
+    ///
+    /// This is O(1) unless values have been removed from the middle of the set since it was
+    /// last compacted, in which case it is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let set: OrderedHashSet<_> = ["a", "b"].iter().cloned().collect();
+    /// assert_eq!(set.get_index(1), Some(&"b"));
+    /// assert_eq!(set.get_index(2), None);
+    /// ```
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.map.get_index(index).map(|(k, _)| k)
+    }
+
+    /// Removes the gaps left by values removed from the middle of the set.
+    ///
+    /// This is O(n) when there are gaps. Afterwards [`get_index`] and [`get_index_of`] are O(1)
+    /// rather than O(log n) until the next removal from the middle.
+    ///
+    /// [`get_index`]: #method.get_index
+    /// [`get_index_of`]: #method.get_index_of
+    #[inline]
+    pub fn compact(&mut self) {
+        self.map.compact();
+    }
+
+    /// Stops tracking positions across gaps, for sets that are never read by position.
+    #[inline]
+    pub(crate) fn unranked(self) -> Self {
+        OrderedHashSet {
+            map: self.map.unranked(),
+        }
+    }
+
+    /// Swaps the positions of the values at `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` are out of bounds.
+    #[inline]
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.map.swap_indices(a, b);
+    }
+
+    /// Moves the value at `from` to `to`, shifting the values in between by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` are out of bounds.
+    #[inline]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.map.move_index(from, to);
+    }
+}
+
+impl<T, S> OrderedHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Reserves capacity for at least `additional` more elements to be inserted
+    /// in the `OrderedHashSet`.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit();
+    }
+
+    /// Adds a value to the set.
+    ///
+    /// If the set did not have this value present, it is appended to the end of the order and
+    /// `true` is returned. If it was present, the set and its order are left untouched and
+    /// `false` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut set = OrderedHashSet::new();
+    ///
+    /// assert_eq!(set.insert(2), true);
+    /// assert_eq!(set.insert(1), true);
+    /// assert_eq!(set.insert(2), false);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [&2, &1]);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.map.entry(value) {
+            map::Entry::Occupied(_) => false,
+            map::Entry::Vacant(entry) => {
+                entry.insert(());
+                true
+            }
+        }
+    }
+
+    /// Returns `true` if the set contains a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let set: OrderedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// assert_eq!(set.contains(&1), true);
+    /// assert_eq!(set.contains(&4), false);
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Returns a reference to the value in the set, if any, that is equal to the given value.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get_key_value(value).map(|(k, _)| k)
+    }
+
+    /// Returns the position of the value in the order.
+    ///
+    /// This is O(1) unless values have been removed from the middle of the set since it was
+    /// last compacted, in which case it is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let set: OrderedHashSet<_> = ["a", "b"].iter().cloned().collect();
+    /// assert_eq!(set.get_index_of(&"b"), Some(1));
+    /// assert_eq!(set.get_index_of(&"z"), None);
+    /// ```
+    #[inline]
+    pub fn get_index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get_index_of(value)
+    }
+
+    /// Removes a value from the set. Returns whether the value was present in the set.
+    ///
+    /// The remaining values keep their relative order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut set = OrderedHashSet::new();
+    ///
+    /// set.insert(2);
+    /// assert_eq!(set.remove(&2), true);
+    /// assert_eq!(set.remove(&2), false);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.shift_remove(value).is_some()
+    }
+
+    /// Removes and returns the value in the set, if any, that is equal to the given one.
+    ///
+    /// The remaining values keep their relative order.
+    #[inline]
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.shift_remove_entry(value).map(|(k, _)| k)
+    }
+
+    /// Removes a value from the set by moving the last value into its place. Returns whether
+    /// the value was present in the set.
+    #[inline]
+    pub fn swap_remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.swap_remove(value).is_some()
+    }
+
+    /// Moves an existing value to the end of the order. Returns whether the value was present.
+    #[inline]
+    pub fn move_to_end<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.move_to_end(value)
+    }
+
+    /// Moves an existing value to the front of the order. Returns whether the value was
+    /// present.
+    #[inline]
+    pub fn move_to_front<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.move_to_front(value)
+    }
+
+    /// Retains only the values specified by the predicate, keeping their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut set: OrderedHashSet<_> = (1..=6).collect();
+    /// set.retain(|&k| k % 2 == 0);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [&2, &4, &6]);
+    /// ```
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map.retain(|k, _| f(k));
+    }
+
+    /// Visits the values representing the difference,
+    /// i.e., the values that are in `self` but not in `other`, in the order of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// let a: OrderedHashSet<_> = [3, 1, 2].iter().cloned().collect();
+    /// let b: OrderedHashSet<_> = [4, 2, 3].iter().cloned().collect();
+    ///
+    /// let diff: Vec<_> = a.difference(&b).collect();
+    /// assert_eq!(diff, [&1]);
+    ///
+    /// let diff: Vec<_> = b.difference(&a).collect();
+    /// assert_eq!(diff, [&4]);
+    /// ```
+    #[inline]
+    pub fn difference<'a>(&'a self, other: &'a OrderedHashSet<T, S>) -> Difference<'a, T, S> {
+        Difference {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Visits the values representing the symmetric difference,
+    /// i.e., the values that are in `self` or in `other` but not in both.
+    ///
+    /// The values of `self` come first, in its order, followed by those of `other`, in its
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// let a: OrderedHashSet<_> = [3, 1, 2].iter().cloned().collect();
+    /// let b: OrderedHashSet<_> = [4, 2, 3, 5].iter().cloned().collect();
+    ///
+    /// let diff: Vec<_> = a.symmetric_difference(&b).collect();
+    /// assert_eq!(diff, [&1, &4, &5]);
+    /// ```
+    #[inline]
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a OrderedHashSet<T, S>,
+    ) -> SymmetricDifference<'a, T, S> {
+        SymmetricDifference {
+            iter: self.difference(other).chain(other.difference(self)),
+        }
+    }
+
+    /// Visits the values representing the intersection,
+    /// i.e., the values that are both in `self` and `other`, in the order of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// let a: OrderedHashSet<_> = [3, 1, 2].iter().cloned().collect();
+    /// let b: OrderedHashSet<_> = [4, 2, 3].iter().cloned().collect();
+    ///
+    /// let intersection: Vec<_> = a.intersection(&b).collect();
+    /// assert_eq!(intersection, [&3, &2]);
+    /// ```
+    #[inline]
+    pub fn intersection<'a>(&'a self, other: &'a OrderedHashSet<T, S>) -> Intersection<'a, T, S> {
+        Intersection {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Visits the values representing the union,
+    /// i.e., all the values in `self` or `other`, without duplicates.
+    ///
+    /// The values of `self` come first, in its order, followed by the values only in `other`,
+    /// in its order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// let a: OrderedHashSet<_> = [3, 1, 2].iter().cloned().collect();
+    /// let b: OrderedHashSet<_> = [4, 2, 3].iter().cloned().collect();
+    ///
+    /// let union: Vec<_> = a.union(&b).collect();
+    /// assert_eq!(union, [&3, &1, &2, &4]);
+    /// ```
+    #[inline]
+    pub fn union<'a>(&'a self, other: &'a OrderedHashSet<T, S>) -> Union<'a, T, S> {
+        Union {
+            iter: self.iter().chain(other.difference(self)),
+        }
+    }
+
+    /// Returns `true` if `self` has no values in common with `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let a: OrderedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// let mut b = OrderedHashSet::new();
+    ///
+    /// assert_eq!(a.is_disjoint(&b), true);
+    /// b.insert(4);
+    /// assert_eq!(a.is_disjoint(&b), true);
+    /// b.insert(1);
+    /// assert_eq!(a.is_disjoint(&b), false);
+    /// ```
+    pub fn is_disjoint(&self, other: &OrderedHashSet<T, S>) -> bool {
+        if self.len() <= other.len() {
+            self.iter().all(|v| !other.contains(v))
+        } else {
+            other.iter().all(|v| !self.contains(v))
+        }
+    }
+
+    /// Returns `true` if every value of `self` is in `other`, regardless of order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let sup: OrderedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// let mut set = OrderedHashSet::new();
+    ///
+    /// assert_eq!(set.is_subset(&sup), true);
+    /// set.insert(2);
+    /// assert_eq!(set.is_subset(&sup), true);
+    /// set.insert(4);
+    /// assert_eq!(set.is_subset(&sup), false);
+    /// ```
+    pub fn is_subset(&self, other: &OrderedHashSet<T, S>) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+
+    /// Returns `true` if every value of `other` is in `self`, regardless of order.
+    #[inline]
+    pub fn is_superset(&self, other: &OrderedHashSet<T, S>) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if both sets hold the same values, regardless of their order.
+    ///
+    /// `==` between two `OrderedHashSet`s also compares the order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let a: OrderedHashSet<_> = [1, 2].iter().cloned().collect();
+    /// let b: OrderedHashSet<_> = [2, 1].iter().cloned().collect();
+    /// assert!(a != b);
+    /// assert!(a.eq_unordered(&b));
+    /// ```
+    #[inline]
+    pub fn eq_unordered(&self, other: &OrderedHashSet<T, S>) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<T, S> BitOr<&OrderedHashSet<T, S>> for &OrderedHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = OrderedHashSet<T, S>;
+
+    /// Returns the union of `self` and `rhs` as a new `OrderedHashSet<T, S>`, in the order of
+    /// [`union`].
+    ///
+    /// [`union`]: struct.OrderedHashSet.html#method.union
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let a = OrderedHashSet::from([3, 1, 2]);
+    /// let b = OrderedHashSet::from([4, 2, 3]);
+    /// assert_eq!(&a | &b, OrderedHashSet::from([3, 1, 2, 4]));
+    /// ```
+    fn bitor(self, rhs: &OrderedHashSet<T, S>) -> OrderedHashSet<T, S> {
+        self.union(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> BitAnd<&OrderedHashSet<T, S>> for &OrderedHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = OrderedHashSet<T, S>;
+
+    /// Returns the intersection of `self` and `rhs` as a new `OrderedHashSet<T, S>`, in the
+    /// order of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let a = OrderedHashSet::from([3, 1, 2]);
+    /// let b = OrderedHashSet::from([4, 2, 3]);
+    /// assert_eq!(&a & &b, OrderedHashSet::from([3, 2]));
+    /// ```
+    fn bitand(self, rhs: &OrderedHashSet<T, S>) -> OrderedHashSet<T, S> {
+        self.intersection(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> Sub<&OrderedHashSet<T, S>> for &OrderedHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = OrderedHashSet<T, S>;
+
+    /// Returns the difference of `self` and `rhs` as a new `OrderedHashSet<T, S>`, in the
+    /// order of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let a = OrderedHashSet::from([3, 1, 2]);
+    /// let b = OrderedHashSet::from([4, 2, 3]);
+    /// assert_eq!(&a - &b, OrderedHashSet::from([1]));
+    /// ```
+    fn sub(self, rhs: &OrderedHashSet<T, S>) -> OrderedHashSet<T, S> {
+        self.difference(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> BitXor<&OrderedHashSet<T, S>> for &OrderedHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = OrderedHashSet<T, S>;
+
+    /// Returns the symmetric difference of `self` and `rhs` as a new `OrderedHashSet<T, S>`,
+    /// in the order of [`symmetric_difference`].
+    ///
+    /// [`symmetric_difference`]: struct.OrderedHashSet.html#method.symmetric_difference
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let a = OrderedHashSet::from([3, 1, 2]);
+    /// let b = OrderedHashSet::from([4, 2, 3, 5]);
+    /// assert_eq!(&a ^ &b, OrderedHashSet::from([1, 4, 5]));
+    /// ```
+    fn bitxor(self, rhs: &OrderedHashSet<T, S>) -> OrderedHashSet<T, S> {
+        self.symmetric_difference(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> BitOrAssign<&OrderedHashSet<T, S>> for OrderedHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher,
+{
+    /// Adds the values of `rhs` that are missing from `self`, appending them in the order of
+    /// `rhs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut a = OrderedHashSet::from([3, 1, 2]);
+    /// a |= &OrderedHashSet::from([4, 2, 3]);
+    /// assert_eq!(a, OrderedHashSet::from([3, 1, 2, 4]));
+    /// ```
+    fn bitor_assign(&mut self, rhs: &OrderedHashSet<T, S>) {
+        for value in rhs {
+            if !self.contains(value) {
+                self.insert(value.clone());
+            }
+        }
+    }
+}
+
+impl<T, S> BitAndAssign<&OrderedHashSet<T, S>> for OrderedHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Removes the values of `self` that are not in `rhs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut a = OrderedHashSet::from([3, 1, 2]);
+    /// a &= &OrderedHashSet::from([4, 2, 3]);
+    /// assert_eq!(a, OrderedHashSet::from([3, 2]));
+    /// ```
+    fn bitand_assign(&mut self, rhs: &OrderedHashSet<T, S>) {
+        self.retain(|value| rhs.contains(value));
+    }
+}
+
+impl<T, S> SubAssign<&OrderedHashSet<T, S>> for OrderedHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Removes the values of `self` that are in `rhs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut a = OrderedHashSet::from([3, 1, 2]);
+    /// a -= &OrderedHashSet::from([4, 2, 3]);
+    /// assert_eq!(a, OrderedHashSet::from([1]));
+    /// ```
+    fn sub_assign(&mut self, rhs: &OrderedHashSet<T, S>) {
+        if rhs.len() < self.len() {
+            for value in rhs {
+                self.remove(value);
+            }
+        } else {
+            self.retain(|value| !rhs.contains(value));
+        }
+    }
+}
+
+impl<T, S> BitXorAssign<&OrderedHashSet<T, S>> for OrderedHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher,
+{
+    /// Removes the values of `self` that are in `rhs` and appends the values of `rhs` that are
+    /// not in `self`, in the order of `rhs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let mut a = OrderedHashSet::from([3, 1, 2]);
+    /// a ^= &OrderedHashSet::from([4, 2, 3, 5]);
+    /// assert_eq!(a, OrderedHashSet::from([1, 4, 5]));
+    /// ```
+    fn bitxor_assign(&mut self, rhs: &OrderedHashSet<T, S>) {
+        let added: Vec<T> = rhs.difference(self).cloned().collect();
+        self.retain(|value| !rhs.contains(value));
+        self.extend(added);
+    }
+}
+
+impl<T, S> Default for OrderedHashSet<T, S>
+where
+    S: Default,
+{
+    /// Creates an empty `OrderedHashSet<T, S>` with the `Default` value for the hasher.
+    #[inline]
+    fn default() -> OrderedHashSet<T, S> {
+        OrderedHashSet {
+            map: OrderedHashMap::default(),
+        }
+    }
+}
+
+impl<T, S> Index<usize> for OrderedHashSet<T, S> {
+    type Output = T;
+
+    /// Returns a reference to the value at the supplied position in the order.
+    ///
+    /// This has the same cost as [`get_index`]: O(1), or O(log n) if values have been removed
+    /// from the middle since the set was last compacted.
+    ///
+    /// [`get_index`]: struct.OrderedHashSet.html#method.get_index
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.get_index(index).expect("index out of bounds")
+    }
+}
+
+impl<T, S1, S2> PartialEq<OrderedHashSet<T, S2>> for OrderedHashSet<T, S1>
+where
+    T: PartialEq,
+{
+    /// Compares the values of both sets in order. Use [`eq_unordered`] to ignore the order.
+    ///
+    /// [`eq_unordered`]: #method.eq_unordered
+    #[inline]
+    fn eq(&self, other: &OrderedHashSet<T, S2>) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T, S> Eq for OrderedHashSet<T, S> where T: Eq {}
+
+impl<T, S> Hash for OrderedHashSet<T, S>
+where
+    T: Hash,
+{
+    /// Hashes the values in order, so sets that are equal hash the same.
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.map.hash(state);
+    }
+}
+
+impl<T, S> fmt::Debug for OrderedHashSet<T, S>
+where
+    T: fmt::Debug,
+{
+    /// Formats the set as `{v, ...}` in insertion order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S> FromIterator<T> for OrderedHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    /// Creates a set from the values in order. A repeated value keeps the position of its
+    /// first occurrence.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> OrderedHashSet<T, S> {
+        let mut set = OrderedHashSet::with_hasher(Default::default());
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T, S> Extend<T> for OrderedHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T, S> Extend<&'a T> for OrderedHashSet<T, S>
+where
+    T: 'a + Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for OrderedHashSet<T, RandomState>
+where
+    T: Eq + Hash,
+{
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    ///
+    /// let set = OrderedHashSet::from([3, 1, 3, 2]);
+    /// assert_eq!(format!("{:?}", set), "{3, 1, 2}");
+    /// ```
+    fn from(arr: [T; N]) -> Self {
+        IntoIterator::into_iter(arr).collect()
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a OrderedHashSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, S> IntoIterator for OrderedHashSet<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Creates a consuming iterator, that is, one that moves each value out
+    /// of the set in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::OrderedHashSet;
+    /// let mut set = OrderedHashSet::new();
+    /// set.insert("b".to_string());
+    /// set.insert("a".to_string());
+    ///
+    /// let v: Vec<String> = set.into_iter().collect();
+    /// assert_eq!(v, ["b", "a"]);
+    /// ```
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+/// Implements the iterator traits for a set iterator that wraps a map iterator in `iter`,
+/// keeping only the key.
+macro_rules! key_iterator {
+    ([$($gen:tt)*] $name:ty, $item:ty) => {
+        impl<$($gen)*> Iterator for $name {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next().map(|(k, _)| k)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl<$($gen)*> DoubleEndedIterator for $name {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back().map(|(k, _)| k)
+            }
+        }
+
+        impl<$($gen)*> ExactSizeIterator for $name {
+            #[inline]
+            fn len(&self) -> usize {
+                self.iter.len()
+            }
+        }
+
+        impl<$($gen)*> FusedIterator for $name {}
+    };
+}
+
+/// An iterator over the values of an `OrderedHashSet`.
+///
+/// This `struct` is created by the [`iter`] method on [`OrderedHashSet`].
+///
+/// [`iter`]: struct.OrderedHashSet.html#method.iter
+/// [`OrderedHashSet`]: struct.OrderedHashSet.html
+pub struct Iter<'a, T> {
+    iter: map::Keys<'a, T, ()>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// An owning iterator over the values of an `OrderedHashSet`.
+///
+/// This `struct` is created by the [`into_iter`] method on [`OrderedHashSet`]
+/// (provided by the `IntoIterator` trait).
+///
+/// [`into_iter`]: struct.OrderedHashSet.html#method.into_iter
+/// [`OrderedHashSet`]: struct.OrderedHashSet.html
+pub struct IntoIter<T> {
+    iter: map::IntoIter<T, ()>,
+}
+
+key_iterator!([T] IntoIter<T>, T);
+
+/// A draining iterator over a range of values of an `OrderedHashSet`.
+///
+/// This `struct` is created by the [`drain`] method on [`OrderedHashSet`].
+///
+/// [`drain`]: struct.OrderedHashSet.html#method.drain
+/// [`OrderedHashSet`]: struct.OrderedHashSet.html
+pub struct Drain<'a, T> {
+    iter: map::Drain<'a, T, ()>,
+}
+
+key_iterator!(['a, T] Drain<'a, T>, T);
+
+/// A lazy iterator producing elements in the intersection of `OrderedHashSet`s.
+///
+/// This `struct` is created by the [`intersection`] method on [`OrderedHashSet`].
+///
+/// [`intersection`]: struct.OrderedHashSet.html#method.intersection
+/// [`OrderedHashSet`]: struct.OrderedHashSet.html
+pub struct Intersection<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a OrderedHashSet<T, S>,
+}
+
+impl<T, S> Clone for Intersection<'_, T, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Intersection {
+            iter: self.iter.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<'a, T, S> Iterator for Intersection<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.by_ref().find(|v| other.contains(v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<T, S> FusedIterator for Intersection<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+/// A lazy iterator producing elements in the difference of `OrderedHashSet`s.
+///
+/// This `struct` is created by the [`difference`] method on [`OrderedHashSet`].
+///
+/// [`difference`]: struct.OrderedHashSet.html#method.difference
+/// [`OrderedHashSet`]: struct.OrderedHashSet.html
+pub struct Difference<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a OrderedHashSet<T, S>,
+}
+
+impl<T, S> Clone for Difference<'_, T, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Difference {
+            iter: self.iter.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<'a, T, S> Iterator for Difference<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.by_ref().find(|v| !other.contains(v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<T, S> FusedIterator for Difference<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+/// A lazy iterator producing elements in the symmetric difference of `OrderedHashSet`s.
+///
+/// This `struct` is created by the [`symmetric_difference`] method on [`OrderedHashSet`].
+///
+/// [`symmetric_difference`]: struct.OrderedHashSet.html#method.symmetric_difference
+/// [`OrderedHashSet`]: struct.OrderedHashSet.html
+pub struct SymmetricDifference<'a, T, S> {
+    iter: Chain<Difference<'a, T, S>, Difference<'a, T, S>>,
+}
+
+impl<T, S> Clone for SymmetricDifference<'_, T, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        SymmetricDifference {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<'a, T, S> Iterator for SymmetricDifference<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, S> FusedIterator for SymmetricDifference<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+/// A lazy iterator producing elements in the union of `OrderedHashSet`s.
+///
+/// This `struct` is created by the [`union`] method on [`OrderedHashSet`].
+///
+/// [`union`]: struct.OrderedHashSet.html#method.union
+/// [`OrderedHashSet`]: struct.OrderedHashSet.html
+pub struct Union<'a, T, S> {
+    iter: Chain<Iter<'a, T>, Difference<'a, T, S>>,
+}
+
+impl<T, S> Clone for Union<'_, T, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Union {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<'a, T, S> Iterator for Union<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, S> FusedIterator for Union<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderedHashSet;
+
+    fn values<T>(iter: impl Iterator<Item = T>) -> Vec<T> {
+        iter.collect()
+    }
+
+    #[test]
+    fn insert_remove_and_positions() {
+        let mut set = OrderedHashSet::new();
+        for v in [5, 3, 5, 1, 4, 3].iter() {
+            set.insert(*v);
+        }
+        assert_eq!(values(set.iter().cloned()), vec![5, 3, 1, 4]);
+        assert!(set.remove(&3));
+        assert!(!set.remove(&3));
+        assert_eq!(set.get_index_of(&1), Some(1));
+        assert_eq!(set[2], 4);
+        assert!(set.insert(3));
+        assert_eq!(values(set.iter().cloned()), vec![5, 1, 4, 3]);
+        assert!(set.swap_remove(&5));
+        assert_eq!(values(set.iter().cloned()), vec![3, 1, 4]);
+        assert_eq!(set.take(&1), Some(1));
+        assert_eq!(set.get(&4), Some(&4));
+        assert_eq!(values(set.into_iter()), vec![3, 4]);
+    }
+
+    #[test]
+    fn set_algebra_is_left_first() {
+        let a: OrderedHashSet<_> = "dcba".chars().collect();
+        let b: OrderedHashSet<_> = "xbyd".chars().collect();
+
+        assert_eq!(
+            values(a.union(&b).cloned()),
+            vec!['d', 'c', 'b', 'a', 'x', 'y']
+        );
+        assert_eq!(
+            values(b.union(&a).cloned()),
+            vec!['x', 'b', 'y', 'd', 'c', 'a']
+        );
+        assert_eq!(values(a.intersection(&b).cloned()), vec!['d', 'b']);
+        assert_eq!(values(b.intersection(&a).cloned()), vec!['b', 'd']);
+        assert_eq!(values(a.difference(&b).cloned()), vec!['c', 'a']);
+        assert_eq!(
+            values(a.symmetric_difference(&b).cloned()),
+            vec!['c', 'a', 'x', 'y']
+        );
+
+        let sub: OrderedHashSet<_> = "bd".chars().collect();
+        assert!(sub.is_subset(&a) && sub.is_subset(&b));
+        assert!(a.is_superset(&sub));
+        assert!(!a.is_subset(&b));
+        assert!(!a.is_disjoint(&b));
+        assert!(sub.is_disjoint(&"ac".chars().collect()));
+
+        let reversed: OrderedHashSet<_> = "abcd".chars().collect();
+        assert!(a != reversed);
+        assert!(a.eq_unordered(&reversed));
+    }
+
+    #[test]
+    fn operators_match_set_algebra() {
+        let a: OrderedHashSet<_> = "dcba".chars().collect();
+        let b: OrderedHashSet<_> = "xbyd".chars().collect();
+
+        assert_eq!(values((&a | &b).into_iter()), values(a.union(&b).cloned()));
+        assert_eq!(values((&a & &b).into_iter()), vec!['d', 'b']);
+        assert_eq!(values((&a - &b).into_iter()), vec!['c', 'a']);
+        assert_eq!(values((&a ^ &b).into_iter()), vec!['c', 'a', 'x', 'y']);
+
+        let mut c = a.clone();
+        c |= &b;
+        assert_eq!(c, &a | &b);
+        let mut c = a.clone();
+        c &= &b;
+        assert_eq!(c, &a & &b);
+        let mut c = a.clone();
+        c -= &b;
+        assert_eq!(c, &a - &b);
+        c -= &OrderedHashSet::new();
+        assert_eq!(c, &a - &b);
+        let mut c = a.clone();
+        c ^= &b;
+        assert_eq!(c, &a ^ &b);
+    }
+}
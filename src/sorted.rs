@@ -0,0 +1,506 @@
+//! The storage shared by the sorted collections.
+//!
+//! Items are kept in order in a list of sublists, in the style of Python's
+//! `sortedcontainers`. Each sublist holds between `load / 2` and `2 * load` items, so inserting
+//! or removing only shifts a bounded number of items. A Fenwick tree over the sublist lengths
+//! turns an index into a (sublist, offset) pair and back in O(log n); it is updated in place
+//! when a length changes and rebuilt when sublists are split, merged or dropped, which happens
+//! at most once every `load / 2` updates.
+//!
+//! The core knows nothing about the ordering: callers find positions with `partition_point`
+//! and are responsible for inserting items where they keep the order intact.
+
+use std::iter::FusedIterator;
+use std::slice;
+use std::vec;
+
+/// The sublist length the sorted collections aim for.
+const DEFAULT_LOAD: usize = 512;
+
+#[derive(Clone)]
+pub(crate) struct SortedCore<T> {
+    lists: Vec<Vec<T>>,
+    index: Vec<usize>,
+    len: usize,
+    load: usize,
+}
+
+impl<T> SortedCore<T> {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        SortedCore::with_load(DEFAULT_LOAD)
+    }
+
+    #[inline]
+    pub(crate) fn with_load(load: usize) -> Self {
+        assert!(load >= 2, "load must be at least 2");
+        SortedCore {
+            lists: Vec::new(),
+            index: Vec::new(),
+            len: 0,
+            load,
+        }
+    }
+
+    /// Returns the number of sublists, so tests can check that they split and merge.
+    #[cfg(test)]
+    pub(crate) fn sublists(&self) -> usize {
+        self.lists.len()
+    }
+
+    /// Builds a core from items that are already in order.
+    pub(crate) fn from_sorted(items: Vec<T>) -> Self {
+        let mut core = SortedCore::new();
+        core.len = items.len();
+        let mut items = items.into_iter();
+        while items.len() > 0 {
+            core.lists.push(items.by_ref().take(core.load).collect());
+        }
+        core.rebuild_index();
+        core
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.lists.clear();
+        self.index.clear();
+        self.len = 0;
+    }
+
+    /// Returns the index of the first item for which `pred` is false, assuming it is true for
+    /// every item before that and false for every item after.
+    pub(crate) fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let list = self
+            .lists
+            .partition_point(|items| pred(&items[items.len() - 1]));
+        if list == self.lists.len() {
+            return self.len;
+        }
+        self.prefix(list) + self.lists[list].partition_point(pred)
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let (list, offset) = self.locate(index);
+        Some(&self.lists[list][offset])
+    }
+
+    #[inline]
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let (list, offset) = self.locate(index);
+        Some(&mut self.lists[list][offset])
+    }
+
+    /// Inserts `value` at `index`. The caller makes sure this keeps the items in order.
+    pub(crate) fn insert(&mut self, index: usize, value: T) {
+        assert!(
+            index <= self.len,
+            "index {} is out of bounds for length {}",
+            index,
+            self.len
+        );
+        self.len += 1;
+        if self.lists.is_empty() {
+            self.lists.push(vec![value]);
+            self.rebuild_index();
+            return;
+        }
+
+        let (list, offset) = if index == self.len - 1 {
+            let last = self.lists.len() - 1;
+            (last, self.lists[last].len())
+        } else {
+            self.locate(index)
+        };
+        self.lists[list].insert(offset, value);
+        if self.lists[list].len() > 2 * self.load {
+            let half = self.lists[list].split_off(self.load);
+            self.lists.insert(list + 1, half);
+            self.rebuild_index();
+        } else {
+            self.add(list, 1);
+        }
+    }
+
+    /// Removes and returns the item at `index`.
+    pub(crate) fn remove(&mut self, index: usize) -> T {
+        assert!(
+            index < self.len,
+            "index {} is out of bounds for length {}",
+            index,
+            self.len
+        );
+        let (list, offset) = self.locate(index);
+        let value = self.lists[list].remove(offset);
+        self.len -= 1;
+
+        let remaining = self.lists[list].len();
+        if remaining == 0 {
+            self.lists.remove(list);
+            self.rebuild_index();
+        } else if remaining < self.load / 2 && self.lists.len() > 1 {
+            let merged = if list + 1 < self.lists.len() {
+                list
+            } else {
+                list - 1
+            };
+            let next = self.lists.remove(merged + 1);
+            self.lists[merged].extend(next);
+            if self.lists[merged].len() > 2 * self.load {
+                let half = self.lists[merged].len() / 2;
+                let half = self.lists[merged].split_off(half);
+                self.lists.insert(merged + 1, half);
+            }
+            self.rebuild_index();
+        } else {
+            self.add(list, -1);
+        }
+        value
+    }
+
+    /// Returns an iterator over the items at the indices `start..end`.
+    pub(crate) fn range(&self, start: usize, end: usize) -> Iter<'_, T> {
+        assert!(
+            start <= end && end <= self.len,
+            "range {}..{} is out of bounds for length {}",
+            start,
+            end,
+            self.len
+        );
+        if start == end {
+            return Iter::default();
+        }
+        let (first, from) = self.locate(start);
+        let (last, to) = self.locate(end - 1);
+        if first == last {
+            return Iter {
+                front: self.lists[first][from..=to].iter(),
+                lists: [].iter(),
+                back: [].iter(),
+                remaining: end - start,
+            };
+        }
+        Iter {
+            front: self.lists[first][from..].iter(),
+            lists: self.lists[first + 1..last].iter(),
+            back: self.lists[last][..=to].iter(),
+            remaining: end - start,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: [].iter(),
+            lists: self.lists.iter(),
+            back: [].iter(),
+            remaining: self.len,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: [].iter_mut(),
+            lists: self.lists.iter_mut(),
+            back: [].iter_mut(),
+            remaining: self.len,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            front: Vec::new().into_iter(),
+            lists: self.lists.into_iter(),
+            back: Vec::new().into_iter(),
+            remaining: self.len,
+        }
+    }
+
+    /// Maps an index below `len` to its sublist and the offset within it.
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let mut list = 0;
+        let mut offset = index;
+        let mut step = self.index.len().next_power_of_two();
+        while step > 0 {
+            let next = list + step;
+            if next <= self.index.len() && self.index[next - 1] <= offset {
+                list = next;
+                offset -= self.index[next - 1];
+            }
+            step /= 2;
+        }
+        (list, offset)
+    }
+
+    /// Returns the number of items in the sublists before `list`.
+    fn prefix(&self, list: usize) -> usize {
+        let mut sum = 0;
+        let mut i = list;
+        while i > 0 {
+            sum += self.index[i - 1];
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// Adds `delta` to the length recorded for `list`.
+    fn add(&mut self, list: usize, delta: isize) {
+        let mut i = list + 1;
+        while i <= self.index.len() {
+            self.index[i - 1] = self.index[i - 1].wrapping_add(delta as usize);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn rebuild_index(&mut self) {
+        self.index.clear();
+        self.index.extend(self.lists.iter().map(Vec::len));
+        for i in 1..=self.index.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= self.index.len() {
+                self.index[parent - 1] += self.index[i - 1];
+            }
+        }
+    }
+}
+
+/// Implements the iterator traits for a type that walks the items of `front`, then the items
+/// of each sublist in `lists`, then the items of `back`.
+macro_rules! flat_iterator {
+    ([$($gen:tt)*] $name:ty, $item:ty) => {
+        impl<$($gen)*> Iterator for $name {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                loop {
+                    if let Some(item) = self.front.next() {
+                        self.remaining -= 1;
+                        return Some(item);
+                    }
+                    match self.lists.next() {
+                        Some(list) => self.front = list.into_iter(),
+                        None => {
+                            let item = self.back.next()?;
+                            self.remaining -= 1;
+                            return Some(item);
+                        }
+                    }
+                }
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.remaining, Some(self.remaining))
+            }
+        }
+
+        impl<$($gen)*> DoubleEndedIterator for $name {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                loop {
+                    if let Some(item) = self.back.next_back() {
+                        self.remaining -= 1;
+                        return Some(item);
+                    }
+                    match self.lists.next_back() {
+                        Some(list) => self.back = list.into_iter(),
+                        None => {
+                            let item = self.front.next_back()?;
+                            self.remaining -= 1;
+                            return Some(item);
+                        }
+                    }
+                }
+            }
+        }
+
+        impl<$($gen)*> ExactSizeIterator for $name {
+            #[inline]
+            fn len(&self) -> usize {
+                self.remaining
+            }
+        }
+
+        impl<$($gen)*> FusedIterator for $name {}
+    };
+}
+
+/// Implements the iterator traits for a type that projects the items of the iterator in its
+/// `inner` field.
+macro_rules! projected_iterator {
+    ([$($gen:tt)*] $name:ty, $item:ty, $pat:pat => $map:expr) => {
+        impl<$($gen)*> Iterator for $name {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next().map(|$pat| $map)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+        }
+
+        impl<$($gen)*> DoubleEndedIterator for $name {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.inner.next_back().map(|$pat| $map)
+            }
+        }
+
+        impl<$($gen)*> ExactSizeIterator for $name {
+            #[inline]
+            fn len(&self) -> usize {
+                self.inner.len()
+            }
+        }
+
+        impl<$($gen)*> std::iter::FusedIterator for $name {}
+    };
+}
+
+pub(crate) use projected_iterator;
+
+pub(crate) struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    lists: slice::Iter<'a, Vec<T>>,
+    back: slice::Iter<'a, T>,
+    remaining: usize,
+}
+
+impl<T> Default for Iter<'_, T> {
+    #[inline]
+    fn default() -> Self {
+        Iter {
+            front: [].iter(),
+            lists: [].iter(),
+            back: [].iter(),
+            remaining: 0,
+        }
+    }
+}
+
+impl<T> Clone for Iter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            front: self.front.clone(),
+            lists: self.lists.clone(),
+            back: self.back.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+flat_iterator!(['a, T] Iter<'a, T>, &'a T);
+
+pub(crate) struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    lists: slice::IterMut<'a, Vec<T>>,
+    back: slice::IterMut<'a, T>,
+    remaining: usize,
+}
+
+flat_iterator!(['a, T] IterMut<'a, T>, &'a mut T);
+
+pub(crate) struct IntoIter<T> {
+    front: vec::IntoIter<T>,
+    lists: vec::IntoIter<Vec<T>>,
+    back: vec::IntoIter<T>,
+    remaining: usize,
+}
+
+flat_iterator!([T] IntoIter<T>, T);
+
+#[cfg(test)]
+mod tests {
+    use super::SortedCore;
+
+    fn assert_consistent(core: &SortedCore<usize>) {
+        let items: Vec<_> = core.iter().cloned().collect();
+        assert_eq!(items.len(), core.len());
+        assert!(items.windows(2).all(|w| w[0] <= w[1]));
+        for (i, item) in items.iter().enumerate() {
+            assert_eq!(core.get(i), Some(item));
+            assert_eq!(
+                core.partition_point(|x| x < item),
+                items.partition_point(|x| x < item)
+            );
+        }
+        for list in &core.lists {
+            assert!(!list.is_empty() && list.len() <= 2 * core.load);
+        }
+        assert_eq!(core.get(core.len()), None);
+    }
+
+    #[test]
+    fn split_and_merge_sublists() {
+        let mut core = SortedCore::with_load(4);
+        for i in 0..100 {
+            let value = (i * 37) % 101;
+            let index = core.partition_point(|&x| x < value);
+            core.insert(index, value);
+        }
+        assert!(core.lists.len() > 5);
+        assert_consistent(&core);
+
+        for i in (0..100).step_by(3) {
+            let value = (i * 37) % 101;
+            let index = core.partition_point(|&x| x < value);
+            assert_eq!(core.remove(index), value);
+            assert_consistent(&core);
+        }
+        while core.len() > 0 {
+            core.remove(core.len() / 2);
+        }
+        assert_consistent(&core);
+        assert!(core.lists.is_empty());
+    }
+
+    #[test]
+    fn ranges_and_reverse_iteration() {
+        let mut core = SortedCore::with_load(4);
+        for i in (0..50).chain(0..50) {
+            let index = core.partition_point(|&x| x <= i);
+            core.insert(index, i);
+        }
+        assert!(core.lists.len() > 10);
+        assert!(core.lists.iter().all(|list| list.len() <= 8));
+        let items: Vec<_> = core.iter().cloned().collect();
+        for start in 0..items.len() {
+            for end in start..=items.len() {
+                let range: Vec<_> = core.range(start, end).cloned().collect();
+                assert_eq!(range, &items[start..end]);
+                assert_eq!(core.range(start, end).len(), end - start);
+            }
+        }
+        let mut reversed: Vec<_> = core.iter().rev().cloned().collect();
+        reversed.reverse();
+        assert_eq!(reversed, items);
+
+        for item in core.iter_mut() {
+            *item += 1;
+        }
+        let owned: Vec<_> = core.into_iter().rev().take(2).collect();
+        assert_eq!(owned, [50, 50]);
+    }
+}
@@ -0,0 +1,674 @@
+use crate::sorted::SortedCore;
+use crate::sorted_list::{IntoIter, Iter};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Index;
+
+/// A list kept sorted by a key derived from each item, like Python's
+/// `sortedcontainers.SortedKeyList(key=...)`.
+///
+/// The key function lets items be sorted by one of their fields without a wrapper type that
+/// implements `Ord`. It is called on every comparison, so it should be cheap, and it must
+/// return the same key for an item for as long as the item is in the list. Items with equal
+/// keys are kept in the order they were added.
+///
+/// See [`SortedListBy`] for ordering items with a comparator instead.
+///
+/// [`SortedListBy`]: struct.SortedListBy.html
+///
+/// # Examples
+///
+/// ```
+/// use ordered::SortedKeyList;
+///
+/// struct Player {
+///     name: &'static str,
+///     score: u32,
+/// }
+///
+/// let mut board = SortedKeyList::new(|p: &Player| p.score);
+/// board.add(Player { name: "ann", score: 30 });
+/// board.add(Player { name: "bo", score: 10 });
+/// board.add(Player { name: "cy", score: 20 });
+///
+/// let names: Vec<_> = board.iter().map(|p| p.name).collect();
+/// assert_eq!(names, ["bo", "cy", "ann"]);
+/// assert_eq!(board.bisect_key_left(&20), 1);
+/// ```
+#[derive(Clone)]
+pub struct SortedKeyList<T, F> {
+    core: SortedCore<T>,
+    key: F,
+}
+
+impl<T, F> SortedKeyList<T, F> {
+    /// Returns the number of items in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Returns `true` if the list contains no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.core.len() == 0
+    }
+
+    /// Removes all items from the list.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Returns the key function of the list.
+    #[inline]
+    pub fn key_fn(&self) -> &F {
+        &self.key
+    }
+
+    /// Returns the item at `index`.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.core.get(index)
+    }
+
+    /// Returns the item with the smallest key.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.core.get(0)
+    }
+
+    /// Returns the item with the largest key.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.core.get(self.len().wrapping_sub(1))
+    }
+
+    /// Removes and returns the item at `index`.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn pop(&mut self, index: usize) -> Option<T> {
+        if index >= self.core.len() {
+            return None;
+        }
+        Some(self.core.remove(index))
+    }
+
+    /// Returns the items at the indices `start..stop`.
+    ///
+    /// `stop` is clamped to the length of the list, and an empty range yields nothing.
+    #[inline]
+    pub fn islice(&self, start: usize, stop: usize) -> Iter<'_, T> {
+        let stop = usize::min(stop, self.len());
+        let start = usize::min(start, stop);
+        Iter {
+            inner: self.core.range(start, stop),
+        }
+    }
+
+    /// An iterator visiting all items in key order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.core.iter(),
+        }
+    }
+}
+
+impl<T, K, F> SortedKeyList<T, F>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    /// Creates an empty `SortedKeyList` that sorts items by `key`.
+    #[inline]
+    pub fn new(key: F) -> SortedKeyList<T, F> {
+        SortedKeyList {
+            core: SortedCore::new(),
+            key,
+        }
+    }
+
+    /// Adds an item, after any items with an equal key.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn add(&mut self, value: T) {
+        let index = self.bisect_key_right(&(self.key)(&value));
+        self.core.insert(index, value);
+    }
+
+    /// Returns the index at which an item with the given key would be added before any
+    /// items with an equal key, like Python's `SortedKeyList.bisect_key_left`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedKeyList;
+    ///
+    /// let mut words = SortedKeyList::new(|w: &&str| w.len());
+    /// words.extend(vec!["ccc", "a", "bb", "dd"]);
+    /// assert_eq!(words.bisect_key_left(&2), 1);
+    /// assert_eq!(words.bisect_key_right(&2), 3);
+    /// ```
+    #[inline]
+    pub fn bisect_key_left(&self, key: &K) -> usize {
+        let key_of = &self.key;
+        self.core.partition_point(|item| key_of(item) < *key)
+    }
+
+    /// Returns the index at which an item with the given key would be added after any
+    /// items with an equal key, like Python's `SortedKeyList.bisect_key_right`.
+    #[inline]
+    pub fn bisect_key_right(&self, key: &K) -> usize {
+        let key_of = &self.key;
+        self.core.partition_point(|item| key_of(item) <= *key)
+    }
+
+    /// Returns the index at which `value` would be added before any items with an equal
+    /// key.
+    #[inline]
+    pub fn bisect_left(&self, value: &T) -> usize {
+        self.bisect_key_left(&(self.key)(value))
+    }
+
+    /// Returns the index at which `value` would be added after any items with an equal key.
+    #[inline]
+    pub fn bisect_right(&self, value: &T) -> usize {
+        self.bisect_key_right(&(self.key)(value))
+    }
+
+    /// Returns the items whose keys lie between `min` and `max`, like Python's
+    /// `SortedKeyList.irange_key(min_key, max_key, inclusive)`.
+    ///
+    /// A bound of `None` leaves that side open. `inclusive` says whether keys equal to `min`
+    /// and `max` respectively are included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedKeyList;
+    ///
+    /// let mut words = SortedKeyList::new(|w: &&str| w.len());
+    /// words.extend(vec!["ccc", "a", "bb", "dddd"]);
+    /// let middle: Vec<_> = words.irange_key(Some(&2), Some(&3), (true, true)).collect();
+    /// assert_eq!(middle, [&"bb", &"ccc"]);
+    /// ```
+    pub fn irange_key(
+        &self,
+        min: Option<&K>,
+        max: Option<&K>,
+        inclusive: (bool, bool),
+    ) -> Iter<'_, T> {
+        let start = match min {
+            Some(min) if inclusive.0 => self.bisect_key_left(min),
+            Some(min) => self.bisect_key_right(min),
+            None => 0,
+        };
+        let stop = match max {
+            Some(max) if inclusive.1 => self.bisect_key_right(max),
+            Some(max) => self.bisect_key_left(max),
+            None => self.len(),
+        };
+        self.islice(start, stop)
+    }
+}
+
+impl<T, K, F> SortedKeyList<T, F>
+where
+    T: PartialEq,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    /// Returns the index of the first item equal to `value`, or `None` if there is none.
+    ///
+    /// Only the items with the same key as `value` are compared, so this is O(log n) plus the
+    /// number of such items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedKeyList;
+    ///
+    /// let mut words = SortedKeyList::new(|w: &&str| w.len());
+    /// words.extend(vec!["bb", "a", "cc"]);
+    /// assert_eq!(words.index(&"cc"), Some(2));
+    /// assert_eq!(words.index(&"dd"), None);
+    /// ```
+    #[inline]
+    pub fn index(&self, value: &T) -> Option<usize> {
+        let start = self.bisect_left(value);
+        let stop = self.bisect_right(value);
+        position(&self.core, start, stop, value)
+    }
+
+    /// Returns the number of items equal to `value`.
+    #[inline]
+    pub fn count(&self, value: &T) -> usize {
+        let start = self.bisect_left(value);
+        let stop = self.bisect_right(value);
+        self.core
+            .range(start, stop)
+            .filter(|item| *item == value)
+            .count()
+    }
+
+    /// Returns `true` if the list contains an item equal to `value`.
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool {
+        self.index(value).is_some()
+    }
+
+    /// Removes and returns the first item equal to `value`, if any.
+    #[inline]
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let index = self.index(value)?;
+        Some(self.core.remove(index))
+    }
+
+    /// Removes the first item equal to `value`. Returns whether such an item was present.
+    #[inline]
+    pub fn discard(&mut self, value: &T) -> bool {
+        self.remove(value).is_some()
+    }
+}
+
+/// A list kept sorted by a comparator, for orderings that are not based on a key.
+///
+/// The comparator must be a total order and must not change its mind about two items while
+/// they are in the list. Items that compare equal are kept in the order they were added.
+///
+/// # Examples
+///
+/// ```
+/// use ordered::SortedListBy;
+///
+/// // Sorted by length, longest first, then alphabetically.
+/// let mut words = SortedListBy::new(|a: &&str, b: &&str| b.len().cmp(&a.len()).then(a.cmp(b)));
+/// words.extend(vec!["fig", "apple", "kiwi", "date"]);
+/// let sorted: Vec<_> = words.iter().cloned().collect();
+/// assert_eq!(sorted, ["apple", "date", "kiwi", "fig"]);
+/// ```
+#[derive(Clone)]
+pub struct SortedListBy<T, C> {
+    core: SortedCore<T>,
+    compare: C,
+}
+
+impl<T, C> SortedListBy<T, C> {
+    /// Returns the number of items in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Returns `true` if the list contains no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.core.len() == 0
+    }
+
+    /// Removes all items from the list.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Returns the comparator of the list.
+    #[inline]
+    pub fn compare_fn(&self) -> &C {
+        &self.compare
+    }
+
+    /// Returns the item at `index`.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.core.get(index)
+    }
+
+    /// Returns the smallest item.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.core.get(0)
+    }
+
+    /// Returns the largest item.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.core.get(self.len().wrapping_sub(1))
+    }
+
+    /// Removes and returns the item at `index`.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn pop(&mut self, index: usize) -> Option<T> {
+        if index >= self.core.len() {
+            return None;
+        }
+        Some(self.core.remove(index))
+    }
+
+    /// Returns the items at the indices `start..stop`.
+    ///
+    /// `stop` is clamped to the length of the list, and an empty range yields nothing.
+    #[inline]
+    pub fn islice(&self, start: usize, stop: usize) -> Iter<'_, T> {
+        let stop = usize::min(stop, self.len());
+        let start = usize::min(start, stop);
+        Iter {
+            inner: self.core.range(start, stop),
+        }
+    }
+
+    /// An iterator visiting all items in order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.core.iter(),
+        }
+    }
+}
+
+impl<T, C> SortedListBy<T, C>
+where
+    C: Fn(&T, &T) -> Ordering,
+{
+    /// Creates an empty `SortedListBy` that orders items with `compare`.
+    #[inline]
+    pub fn new(compare: C) -> SortedListBy<T, C> {
+        SortedListBy {
+            core: SortedCore::new(),
+            compare,
+        }
+    }
+
+    /// Creates an empty `SortedListBy` whose sublists aim for `load` items.
+    #[cfg(test)]
+    pub(crate) fn with_load(compare: C, load: usize) -> SortedListBy<T, C> {
+        SortedListBy {
+            core: SortedCore::with_load(load),
+            compare,
+        }
+    }
+
+    /// Adds an item, after any items that compare equal to it.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn add(&mut self, value: T) {
+        let index = self.bisect_right(&value);
+        self.core.insert(index, value);
+    }
+
+    /// Returns the index at which `value` would be added before any items that compare equal
+    /// to it.
+    #[inline]
+    pub fn bisect_left(&self, value: &T) -> usize {
+        let compare = &self.compare;
+        self.core
+            .partition_point(|item| compare(item, value) == Ordering::Less)
+    }
+
+    /// Returns the index at which `value` would be added after any items that compare equal
+    /// to it.
+    #[inline]
+    pub fn bisect_right(&self, value: &T) -> usize {
+        let compare = &self.compare;
+        self.core
+            .partition_point(|item| compare(item, value) != Ordering::Greater)
+    }
+
+    /// Returns the items that lie between `min` and `max` according to the comparator.
+    ///
+    /// A bound of `None` leaves that side open. `inclusive` says whether items that compare
+    /// equal to `min` and `max` respectively are included.
+    pub fn irange(&self, min: Option<&T>, max: Option<&T>, inclusive: (bool, bool)) -> Iter<'_, T> {
+        let start = match min {
+            Some(min) if inclusive.0 => self.bisect_left(min),
+            Some(min) => self.bisect_right(min),
+            None => 0,
+        };
+        let stop = match max {
+            Some(max) if inclusive.1 => self.bisect_right(max),
+            Some(max) => self.bisect_left(max),
+            None => self.len(),
+        };
+        self.islice(start, stop)
+    }
+}
+
+impl<T, C> SortedListBy<T, C>
+where
+    T: PartialEq,
+    C: Fn(&T, &T) -> Ordering,
+{
+    /// Returns the index of the first item equal to `value`, or `None` if there is none.
+    ///
+    /// Only the items that compare equal to `value` are checked with `==`, so this is
+    /// O(log n) plus the number of such items.
+    #[inline]
+    pub fn index(&self, value: &T) -> Option<usize> {
+        let start = self.bisect_left(value);
+        let stop = self.bisect_right(value);
+        position(&self.core, start, stop, value)
+    }
+
+    /// Returns the number of items equal to `value`.
+    #[inline]
+    pub fn count(&self, value: &T) -> usize {
+        let start = self.bisect_left(value);
+        let stop = self.bisect_right(value);
+        self.core
+            .range(start, stop)
+            .filter(|item| *item == value)
+            .count()
+    }
+
+    /// Returns `true` if the list contains an item equal to `value`.
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool {
+        self.index(value).is_some()
+    }
+
+    /// Removes and returns the first item equal to `value`, if any.
+    #[inline]
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let index = self.index(value)?;
+        Some(self.core.remove(index))
+    }
+
+    /// Removes the first item equal to `value`. Returns whether such an item was present.
+    #[inline]
+    pub fn discard(&mut self, value: &T) -> bool {
+        self.remove(value).is_some()
+    }
+}
+
+/// Returns the index of the first item equal to `value` among the indices `start..stop`.
+#[inline]
+fn position<T: PartialEq>(
+    core: &SortedCore<T>,
+    start: usize,
+    stop: usize,
+    value: &T,
+) -> Option<usize> {
+    core.range(start, stop)
+        .position(|item| item == value)
+        .map(|offset| start + offset)
+}
+
+impl<T, K, F> Extend<T> for SortedKeyList<T, F>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<T, C> Extend<T> for SortedListBy<T, C>
+where
+    C: Fn(&T, &T) -> Ordering,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for SortedKeyList<T, F> {
+    /// Formats the list as `[a, b, ...]` in key order, leaving out the key function.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for SortedListBy<T, C> {
+    /// Formats the list as `[a, b, ...]` in order, leaving out the comparator.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, F> Index<usize> for SortedKeyList<T, F> {
+    type Output = T;
+
+    /// Returns the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("SortedKeyList: index out of bounds")
+    }
+}
+
+impl<T, C> Index<usize> for SortedListBy<T, C> {
+    type Output = T;
+
+    /// Returns the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("SortedListBy: index out of bounds")
+    }
+}
+
+impl<'a, T, F> IntoIterator for &'a SortedKeyList<T, F> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, F> IntoIterator for SortedKeyList<T, F> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            inner: self.core.into_iter(),
+        }
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a SortedListBy<T, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, C> IntoIterator for SortedListBy<T, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            inner: self.core.into_iter(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SortedKeyList, SortedListBy};
+
+    #[test]
+    fn key_list_keeps_ties_in_insertion_order() {
+        let mut list = SortedKeyList::new(|&(score, _): &(u32, char)| score);
+        list.extend(vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (3, 'e')]);
+        let tags: String = list.iter().map(|&(_, tag)| tag).collect();
+        assert_eq!(tags, "bdace");
+
+        assert_eq!(list.bisect_key_left(&2), 2);
+        assert_eq!(list.bisect_key_right(&2), 4);
+        assert_eq!(list.index(&(2, 'c')), Some(3));
+        assert_eq!(list.index(&(2, 'z')), None);
+        assert_eq!(list.count(&(1, 'd')), 1);
+        assert_eq!(list.remove(&(2, 'a')), Some((2, 'a')));
+        assert!(!list.discard(&(2, 'a')));
+        let high: Vec<_> = list.irange_key(Some(&1), None, (false, true)).collect();
+        assert_eq!(high, [&(2, 'c'), &(3, 'e')]);
+        assert_eq!(list[0], (1, 'b'));
+    }
+
+    #[test]
+    fn comparator_list_matches_sorted_vec() {
+        let mut list = SortedListBy::with_load(|a: &i32, b: &i32| b.cmp(a), 4);
+        let mut reference = Vec::new();
+        for i in 0..2000i32 {
+            let value = i.wrapping_mul(7919) % 250;
+            if i % 5 == 4 {
+                let removed = list.remove(&value);
+                let position = reference.iter().position(|&x| x == value);
+                assert_eq!(removed, position.map(|p| reference.remove(p)));
+            } else {
+                list.add(value);
+                reference.push(value);
+                reference.sort_by(|a, b| b.cmp(a));
+            }
+        }
+        assert!(list.core.sublists() > 100);
+        assert!(list.iter().eq(reference.iter()));
+        assert_eq!(
+            list.bisect_left(&100),
+            reference.partition_point(|&x| x > 100)
+        );
+        let range: Vec<_> = list
+            .irange(Some(&50), Some(&40), (true, false))
+            .cloned()
+            .collect();
+        let expected: Vec<_> = reference
+            .iter()
+            .cloned()
+            .filter(|&x| x <= 50 && x > 40)
+            .collect();
+        assert_eq!(range, expected);
+        assert_eq!(list.pop(0), reference.first().cloned());
+    }
+}
@@ -0,0 +1,501 @@
+use crate::sorted::{self, projected_iterator, SortedCore};
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+/// A list that keeps its items sorted and allows duplicates, like Python's
+/// `sortedcontainers.SortedList`.
+///
+/// Adding, removing, looking up by value and looking up by position are all O(log n), which
+/// makes it a good fit for order statistics such as running percentiles. Equal items are
+/// kept in the order they were added.
+///
+/// # Examples
+///
+/// ```
+/// use ordered::SortedList;
+///
+/// let mut latencies = SortedList::new();
+/// for ms in [12, 7, 30, 7, 18].iter() {
+///     latencies.add(*ms);
+/// }
+///
+/// // The median and the 80th percentile.
+/// assert_eq!(latencies[latencies.len() / 2], 12);
+/// assert_eq!(latencies.get(latencies.len() * 4 / 5), Some(&30));
+/// assert_eq!(latencies.count(&7), 2);
+/// assert_eq!(latencies.bisect_right(&12), 3);
+/// ```
+#[derive(Clone)]
+pub struct SortedList<T> {
+    core: SortedCore<T>,
+}
+
+impl<T> SortedList<T> {
+    /// Creates an empty `SortedList`.
+    #[inline]
+    pub fn new() -> SortedList<T> {
+        SortedList {
+            core: SortedCore::new(),
+        }
+    }
+
+    /// Creates an empty `SortedList` whose sublists aim for `load` items.
+    #[cfg(test)]
+    pub(crate) fn with_load(load: usize) -> SortedList<T> {
+        SortedList {
+            core: SortedCore::with_load(load),
+        }
+    }
+
+    /// Returns the number of items in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Returns `true` if the list contains no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.core.len() == 0
+    }
+
+    /// Removes all items from the list.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Returns the item at `index`, like Python's `SortedList[index]`.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.core.get(index)
+    }
+
+    /// Returns the smallest item.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.core.get(0)
+    }
+
+    /// Returns the largest item.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.core.get(self.len().wrapping_sub(1))
+    }
+
+    /// Removes and returns the item at `index`, like Python's `SortedList.pop(index)`.
+    ///
+    /// This is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedList;
+    ///
+    /// let mut list: SortedList<_> = vec![3, 1, 2].into_iter().collect();
+    /// assert_eq!(list.pop(1), Some(2));
+    /// assert_eq!(list.pop(2), None);
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    #[inline]
+    pub fn pop(&mut self, index: usize) -> Option<T> {
+        if index >= self.core.len() {
+            return None;
+        }
+        Some(self.core.remove(index))
+    }
+
+    /// Returns the items at the indices `start..stop`, like Python's
+    /// `SortedList.islice(start, stop)`.
+    ///
+    /// `stop` is clamped to the length of the list, and an empty range yields nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedList;
+    ///
+    /// let list: SortedList<_> = "hello".chars().collect();
+    /// let slice: String = list.islice(1, 4).collect();
+    /// assert_eq!(slice, "hll");
+    /// ```
+    #[inline]
+    pub fn islice(&self, start: usize, stop: usize) -> Iter<'_, T> {
+        let stop = usize::min(stop, self.len());
+        let start = usize::min(start, stop);
+        Iter {
+            inner: self.core.range(start, stop),
+        }
+    }
+
+    /// An iterator visiting all items in order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.core.iter(),
+        }
+    }
+}
+
+impl<T: Ord> SortedList<T> {
+    /// Adds an item, after any items equal to it.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn add(&mut self, value: T) {
+        let index = self.bisect_right(&value);
+        self.core.insert(index, value);
+    }
+
+    /// Returns the index at which `value` would be added before any equal items, like
+    /// Python's `SortedList.bisect_left`.
+    #[inline]
+    pub fn bisect_left<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.core.partition_point(|item| item.borrow() < value)
+    }
+
+    /// Returns the index at which `value` would be added after any equal items, like
+    /// Python's `SortedList.bisect_right`.
+    #[inline]
+    pub fn bisect_right<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.core.partition_point(|item| item.borrow() <= value)
+    }
+
+    /// Returns the number of items equal to `value`.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn count<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.bisect_right(value) - self.bisect_left(value)
+    }
+
+    /// Returns `true` if the list contains an item equal to `value`.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.index(value).is_some()
+    }
+
+    /// Returns the index of the first item equal to `value`, like Python's
+    /// `SortedList.index(value)`, or `None` if there is none.
+    ///
+    /// This is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedList;
+    ///
+    /// let list: SortedList<_> = vec![5, 1, 5, 3].into_iter().collect();
+    /// assert_eq!(list.index(&5), Some(2));
+    /// assert_eq!(list.index(&4), None);
+    /// ```
+    #[inline]
+    pub fn index<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.bisect_left(value);
+        match self.core.get(index) {
+            Some(item) if item.borrow() == value => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Removes and returns the first item equal to `value`, if any.
+    ///
+    /// Python's `SortedList.remove` raises when the value is missing; this returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedList;
+    ///
+    /// let mut list: SortedList<_> = vec![2, 1, 2].into_iter().collect();
+    /// assert_eq!(list.remove(&2), Some(2));
+    /// assert_eq!(list.remove(&4), None);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2]);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.index(value)?;
+        Some(self.core.remove(index))
+    }
+
+    /// Removes the first item equal to `value`, like Python's `SortedList.discard`. Returns
+    /// whether such an item was present.
+    #[inline]
+    pub fn discard<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.remove(value).is_some()
+    }
+
+    /// Returns the items that lie between `min` and `max`, like Python's
+    /// `SortedList.irange(minimum, maximum, inclusive)`.
+    ///
+    /// A bound of `None` leaves that side open. `inclusive` says whether items equal to `min`
+    /// and `max` respectively are included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedList;
+    ///
+    /// let list: SortedList<_> = vec![1, 2, 2, 3, 4].into_iter().collect();
+    /// let items: Vec<_> = list.irange(Some(&2), Some(&4), (true, false)).collect();
+    /// assert_eq!(items, [&2, &2, &3]);
+    /// let items: Vec<_> = list.irange(Some(&2), None, (false, true)).collect();
+    /// assert_eq!(items, [&3, &4]);
+    /// ```
+    pub fn irange<Q>(
+        &self,
+        min: Option<&Q>,
+        max: Option<&Q>,
+        inclusive: (bool, bool),
+    ) -> Iter<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let start = match min {
+            Some(min) if inclusive.0 => self.bisect_left(min),
+            Some(min) => self.bisect_right(min),
+            None => 0,
+        };
+        let stop = match max {
+            Some(max) if inclusive.1 => self.bisect_right(max),
+            Some(max) => self.bisect_left(max),
+            None => self.len(),
+        };
+        self.islice(start, stop)
+    }
+}
+
+impl<T> Default for SortedList<T> {
+    /// Creates an empty `SortedList<T>`.
+    #[inline]
+    fn default() -> SortedList<T> {
+        SortedList::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SortedList<T> {
+    #[inline]
+    fn eq(&self, other: &SortedList<T>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for SortedList<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for SortedList<T> {
+    /// Formats the list as `[a, b, ...]` in order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<usize> for SortedList<T> {
+    type Output = T;
+
+    /// Returns the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("SortedList: index out of bounds")
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedList<T> {
+    /// Builds the list in O(n log n), keeping equal items in iteration order.
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SortedList<T> {
+        let mut items: Vec<_> = iter.into_iter().collect();
+        items.sort();
+        SortedList {
+            core: SortedCore::from_sorted(items),
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for SortedList<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SortedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for SortedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            inner: self.core.into_iter(),
+        }
+    }
+}
+
+/// An iterator over the items of a `SortedList` in order.
+///
+/// This `struct` is created by the [`iter`], [`islice`] and [`irange`] methods on
+/// [`SortedList`], and by the same methods on [`SortedKeyList`] and [`SortedListBy`].
+///
+/// [`iter`]: struct.SortedList.html#method.iter
+/// [`islice`]: struct.SortedList.html#method.islice
+/// [`irange`]: struct.SortedList.html#method.irange
+/// [`SortedList`]: struct.SortedList.html
+/// [`SortedKeyList`]: ../sorted_key_list/struct.SortedKeyList.html
+/// [`SortedListBy`]: ../sorted_key_list/struct.SortedListBy.html
+pub struct Iter<'a, T> {
+    pub(crate) inner: sorted::Iter<'a, T>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+projected_iterator!(['a, T] Iter<'a, T>, &'a T, item => item);
+
+/// An owning iterator over the items of a `SortedList` in order.
+///
+/// This `struct` is created by the [`into_iter`] method on [`SortedList`], [`SortedKeyList`]
+/// and [`SortedListBy`] (provided by the `IntoIterator` trait).
+///
+/// [`into_iter`]: struct.SortedList.html#method.into_iter
+/// [`SortedList`]: struct.SortedList.html
+/// [`SortedKeyList`]: ../sorted_key_list/struct.SortedKeyList.html
+/// [`SortedListBy`]: ../sorted_key_list/struct.SortedListBy.html
+pub struct IntoIter<T> {
+    pub(crate) inner: sorted::IntoIter<T>,
+}
+
+projected_iterator!([T] IntoIter<T>, T, item => item);
+
+#[cfg(test)]
+mod tests {
+    use super::SortedList;
+    use std::cmp::Ordering;
+
+    /// Orders by the number only, so items with the same number are equal.
+    #[derive(Debug)]
+    struct Tagged(u32, char);
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Tagged) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Tagged {}
+
+    impl PartialOrd for Tagged {
+        fn partial_cmp(&self, other: &Tagged) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tagged {
+        fn cmp(&self, other: &Tagged) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn duplicates_keep_insertion_order() {
+        let mut list = SortedList::new();
+        for (i, tag) in "abcdef".chars().enumerate() {
+            list.add(Tagged(i as u32 % 2, tag));
+        }
+        let tags: String = list.iter().map(|t| t.1).collect();
+        assert_eq!(tags, "acebdf");
+        assert_eq!(list.count(&Tagged(1, 'z')), 3);
+        assert_eq!(list.remove(&Tagged(0, 'z')).map(|t| t.1), Some('a'));
+
+        let list: SortedList<_> = "fedcba".chars().map(|c| Tagged(0, c)).collect();
+        let tags: String = list.into_iter().rev().map(|t| t.1).collect();
+        assert_eq!(tags, "abcdef");
+    }
+
+    #[test]
+    fn order_statistics_match_sorted_vec() {
+        let mut list = SortedList::with_load(4);
+        let mut reference = Vec::new();
+        for i in 0..2000u32 {
+            let value = i.wrapping_mul(2_654_435_761) % 100;
+            if i % 4 == 3 {
+                let removed = list.discard(&value);
+                let position = reference.iter().position(|&x| x == value);
+                assert_eq!(removed, position.is_some());
+                if let Some(position) = position {
+                    reference.remove(position);
+                }
+            } else {
+                list.add(value);
+                let index = reference.partition_point(|&x| x <= value);
+                reference.insert(index, value);
+            }
+        }
+        assert!(list.core.sublists() > 100);
+        assert!(list.iter().eq(reference.iter()));
+        for (i, value) in reference.iter().enumerate() {
+            assert_eq!(list[i], *value);
+            assert_eq!(
+                list.bisect_left(value),
+                reference.partition_point(|x| x < value)
+            );
+        }
+        let middle: Vec<_> = list.irange(Some(&40), Some(&60), (false, true)).collect();
+        let expected: Vec<_> = reference.iter().filter(|&&x| x > 40 && x <= 60).collect();
+        assert_eq!(middle, expected);
+        assert!(list.islice(10, 20).eq(reference[10..20].iter()));
+    }
+}
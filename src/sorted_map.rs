@@ -0,0 +1,675 @@
+use crate::sorted::{self, projected_iterator, SortedCore};
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+/// A map that keeps its entries sorted by key, like Python's `sortedcontainers.SortedDict`.
+///
+/// Unlike `BTreeMap`, entries can also be reached by their position in the sorted order:
+/// [`peekitem`], [`popitem`] and [`index`] are O(log n), as are insertion, removal and
+/// lookup by key. Internally the entries live in short sorted sublists with an index over
+/// their lengths.
+///
+/// [`peekitem`]: #method.peekitem
+/// [`popitem`]: #method.popitem
+/// [`index`]: #method.index
+///
+/// # Examples
+///
+/// ```
+/// use ordered::SortedMap;
+///
+/// let mut scores = SortedMap::new();
+/// scores.insert("carol", 7);
+/// scores.insert("alice", 3);
+/// scores.insert("bob", 5);
+///
+/// assert_eq!(scores.peekitem(0), Some((&"alice", &3)));
+/// assert_eq!(scores.index(&"carol"), Some(2));
+/// assert_eq!(scores.bisect_left(&"b"), 1);
+/// let keys: Vec<_> = scores.keys().collect();
+/// assert_eq!(keys, [&"alice", &"bob", &"carol"]);
+/// ```
+#[derive(Clone)]
+pub struct SortedMap<K, V> {
+    core: SortedCore<(K, V)>,
+}
+
+impl<K, V> SortedMap<K, V> {
+    /// Creates an empty `SortedMap`.
+    #[inline]
+    pub fn new() -> SortedMap<K, V> {
+        SortedMap {
+            core: SortedCore::new(),
+        }
+    }
+
+    /// Creates an empty `SortedMap` whose sublists aim for `load` entries.
+    #[cfg(test)]
+    pub(crate) fn with_load(load: usize) -> SortedMap<K, V> {
+        SortedMap {
+            core: SortedCore::with_load(load),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.core.len() == 0
+    }
+
+    /// Removes all entries from the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Returns the entry at `index` in the sorted order, like Python's
+    /// `SortedDict.peekitem(index)`.
+    ///
+    /// This is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let map: SortedMap<_, _> = vec![(3, 'c'), (1, 'a'), (2, 'b')].into_iter().collect();
+    /// assert_eq!(map.peekitem(1), Some((&2, &'b')));
+    /// assert_eq!(map.peekitem(map.len() - 1), Some((&3, &'c')));
+    /// assert_eq!(map.peekitem(3), None);
+    /// ```
+    #[inline]
+    pub fn peekitem(&self, index: usize) -> Option<(&K, &V)> {
+        self.core.get(index).map(|(k, v)| (k, v))
+    }
+
+    /// Returns the entry at `index` in the sorted order, with a mutable value.
+    #[inline]
+    pub fn peekitem_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.core.get_mut(index).map(|(k, v)| (&*k, v))
+    }
+
+    /// Removes and returns the entry at `index` in the sorted order, like Python's
+    /// `SortedDict.popitem(index)`.
+    ///
+    /// This is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let mut map: SortedMap<_, _> = vec![(3, 'c'), (1, 'a'), (2, 'b')].into_iter().collect();
+    /// assert_eq!(map.popitem(map.len() - 1), Some((3, 'c')));
+    /// assert_eq!(map.popitem(0), Some((1, 'a')));
+    /// assert_eq!(map.popitem(1), None);
+    /// ```
+    #[inline]
+    pub fn popitem(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.core.len() {
+            return None;
+        }
+        Some(self.core.remove(index))
+    }
+
+    /// Returns the entry with the smallest key.
+    #[inline]
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.peekitem(0)
+    }
+
+    /// Returns the entry with the largest key.
+    #[inline]
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.peekitem(self.len().wrapping_sub(1))
+    }
+
+    /// Returns the entries at the indices `start..stop` of the sorted order, like Python's
+    /// `SortedDict.islice(start, stop)`.
+    ///
+    /// `stop` is clamped to the length of the map, and an empty range yields nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let map: SortedMap<_, _> = (0..10).map(|i| (i, i * i)).collect();
+    /// let slice: Vec<_> = map.islice(2, 5).map(|(k, _)| *k).collect();
+    /// assert_eq!(slice, [2, 3, 4]);
+    /// assert_eq!(map.islice(8, 100).len(), 2);
+    /// ```
+    #[inline]
+    pub fn islice(&self, start: usize, stop: usize) -> Iter<'_, K, V> {
+        let stop = usize::min(stop, self.len());
+        let start = usize::min(start, stop);
+        Iter {
+            inner: self.core.range(start, stop),
+        }
+    }
+
+    /// An iterator visiting all entries in key order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.core.iter(),
+        }
+    }
+
+    /// An iterator visiting all entries in key order, with mutable references to the values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.core.iter_mut(),
+        }
+    }
+
+    /// An iterator visiting all keys in order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.core.iter(),
+        }
+    }
+
+    /// An iterator visiting all values in key order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            inner: self.core.iter(),
+        }
+    }
+
+    /// An iterator visiting all values mutably in key order.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.core.iter_mut(),
+        }
+    }
+}
+
+impl<K: Ord, V> SortedMap<K, V> {
+    /// Returns the index at which `key` would be inserted, before any equal key, like
+    /// Python's `SortedDict.bisect_left`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let map: SortedMap<_, _> = vec![(10, ()), (20, ()), (30, ())].into_iter().collect();
+    /// assert_eq!(map.bisect_left(&20), 1);
+    /// assert_eq!(map.bisect_left(&25), 2);
+    /// ```
+    #[inline]
+    pub fn bisect_left<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.core.partition_point(|(k, _)| k.borrow() < key)
+    }
+
+    /// Returns the index at which `key` would be inserted, after any equal key, like
+    /// Python's `SortedDict.bisect_right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let map: SortedMap<_, _> = vec![(10, ()), (20, ()), (30, ())].into_iter().collect();
+    /// assert_eq!(map.bisect_right(&20), 2);
+    /// assert_eq!(map.bisect_right(&5), 0);
+    /// ```
+    #[inline]
+    pub fn bisect_right<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.core.partition_point(|(k, _)| k.borrow() <= key)
+    }
+
+    /// Returns the position of `key` in the sorted order, like Python's
+    /// `SortedDict.index(key)`, or `None` if it is not in the map.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.bisect_left(key);
+        match self.core.get(index) {
+            Some((k, _)) if k.borrow() == key => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did have this key present, the value is updated and the old value is
+    /// returned; the key itself is not updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let mut map = SortedMap::new();
+    /// assert_eq!(map.insert(2, "b"), None);
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(2, "c"), Some("b"));
+    /// assert_eq!(map.peekitem(1), Some((&2, &"c")));
+    /// ```
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let index = self.bisect_left(&k);
+        match self.core.get_mut(index) {
+            Some((key, value)) if *key == k => Some(mem::replace(value, v)),
+            _ => {
+                self.core.insert(index, (k, v));
+                None
+            }
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.index(k)?;
+        self.peekitem(index)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.index(k)?;
+        self.core.get_mut(index).map(|(_, v)| v)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.index(k).is_some()
+    }
+
+    /// Removes a key from the map, returning its value if the key was in the map.
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the key was in the
+    /// map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let mut map = SortedMap::new();
+    /// map.insert("a".to_string(), 1);
+    /// assert_eq!(map.remove_entry("a"), Some(("a".to_string(), 1)));
+    /// assert_eq!(map.remove_entry("a"), None);
+    /// ```
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.index(k)?;
+        Some(self.core.remove(index))
+    }
+
+    /// Returns the entries whose keys lie between `min` and `max`, like Python's
+    /// `SortedDict.irange(minimum, maximum, inclusive)`.
+    ///
+    /// A bound of `None` leaves that side open. `inclusive` says whether keys equal to `min`
+    /// and `max` respectively are included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedMap;
+    ///
+    /// let map: SortedMap<_, _> = (1..=9).map(|i| (i, ())).collect();
+    /// let keys = |iter: ordered::sorted_map::Iter<'_, i32, ()>| -> Vec<i32> {
+    ///     iter.map(|(k, _)| *k).collect()
+    /// };
+    /// assert_eq!(keys(map.irange(Some(&3), Some(&6), (true, true))), [3, 4, 5, 6]);
+    /// assert_eq!(keys(map.irange(Some(&3), Some(&6), (false, false))), [4, 5]);
+    /// assert_eq!(keys(map.irange(None, Some(&2), (true, true))), [1, 2]);
+    /// assert_eq!(keys(map.irange(Some(&8), None, (false, true))), [9]);
+    /// ```
+    pub fn irange<Q>(
+        &self,
+        min: Option<&Q>,
+        max: Option<&Q>,
+        inclusive: (bool, bool),
+    ) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let start = match min {
+            Some(min) if inclusive.0 => self.bisect_left(min),
+            Some(min) => self.bisect_right(min),
+            None => 0,
+        };
+        let stop = match max {
+            Some(max) if inclusive.1 => self.bisect_right(max),
+            Some(max) => self.bisect_left(max),
+            None => self.len(),
+        };
+        self.islice(start, stop)
+    }
+}
+
+impl<K, V> Default for SortedMap<K, V> {
+    /// Creates an empty `SortedMap<K, V>`.
+    #[inline]
+    fn default() -> SortedMap<K, V> {
+        SortedMap::new()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for SortedMap<K, V> {
+    #[inline]
+    fn eq(&self, other: &SortedMap<K, V>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SortedMap<K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SortedMap<K, V> {
+    /// Formats the map as `{k: v, ...}` in key order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, Q> Index<&Q> for SortedMap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the map.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, Q> IndexMut<&Q> for SortedMap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    /// Returns a mutable reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the map.
+    #[inline]
+    fn index_mut(&mut self, key: &Q) -> &mut V {
+        self.get_mut(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SortedMap<K, V> {
+    /// Builds the map in O(n log n). Later values win when a key repeats.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> SortedMap<K, V> {
+        let mut entries: Vec<_> = iter.into_iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut deduped: Vec<(K, V)> = Vec::with_capacity(entries.len());
+        for (k, v) in entries {
+            match deduped.last_mut() {
+                Some(last) if last.0 == k => last.1 = v,
+                _ => deduped.push((k, v)),
+            }
+        }
+        SortedMap {
+            core: SortedCore::from_sorted(deduped),
+        }
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SortedMap<K, V> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SortedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut SortedMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for SortedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            inner: self.core.into_iter(),
+        }
+    }
+}
+
+/// An iterator over the entries of a `SortedMap` in key order.
+///
+/// This `struct` is created by the [`iter`], [`islice`] and [`irange`] methods on
+/// [`SortedMap`].
+///
+/// [`iter`]: struct.SortedMap.html#method.iter
+/// [`islice`]: struct.SortedMap.html#method.islice
+/// [`irange`]: struct.SortedMap.html#method.irange
+/// [`SortedMap`]: struct.SortedMap.html
+pub struct Iter<'a, K, V> {
+    inner: sorted::Iter<'a, (K, V)>,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+projected_iterator!(['a, K, V] Iter<'a, K, V>, (&'a K, &'a V), (k, v) => (k, v));
+
+/// A mutable iterator over the entries of a `SortedMap` in key order.
+///
+/// This `struct` is created by the [`iter_mut`] method on [`SortedMap`].
+///
+/// [`iter_mut`]: struct.SortedMap.html#method.iter_mut
+/// [`SortedMap`]: struct.SortedMap.html
+pub struct IterMut<'a, K, V> {
+    inner: sorted::IterMut<'a, (K, V)>,
+}
+
+projected_iterator!(['a, K, V] IterMut<'a, K, V>, (&'a K, &'a mut V), (k, v) => (&*k, v));
+
+/// An owning iterator over the entries of a `SortedMap` in key order.
+///
+/// This `struct` is created by the [`into_iter`] method on [`SortedMap`]
+/// (provided by the `IntoIterator` trait).
+///
+/// [`into_iter`]: struct.SortedMap.html#method.into_iter
+/// [`SortedMap`]: struct.SortedMap.html
+pub struct IntoIter<K, V> {
+    inner: sorted::IntoIter<(K, V)>,
+}
+
+projected_iterator!([K, V] IntoIter<K, V>, (K, V), entry => entry);
+
+/// An iterator over the keys of a `SortedMap` in order.
+///
+/// This `struct` is created by the [`keys`] method on [`SortedMap`].
+///
+/// [`keys`]: struct.SortedMap.html#method.keys
+/// [`SortedMap`]: struct.SortedMap.html
+pub struct Keys<'a, K, V> {
+    inner: sorted::Iter<'a, (K, V)>,
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Keys {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+projected_iterator!(['a, K, V] Keys<'a, K, V>, &'a K, (k, _) => k);
+
+/// An iterator over the values of a `SortedMap` in key order.
+///
+/// This `struct` is created by the [`values`] method on [`SortedMap`].
+///
+/// [`values`]: struct.SortedMap.html#method.values
+/// [`SortedMap`]: struct.SortedMap.html
+pub struct Values<'a, K, V> {
+    inner: sorted::Iter<'a, (K, V)>,
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Values {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+projected_iterator!(['a, K, V] Values<'a, K, V>, &'a V, (_, v) => v);
+
+/// A mutable iterator over the values of a `SortedMap` in key order.
+///
+/// This `struct` is created by the [`values_mut`] method on [`SortedMap`].
+///
+/// [`values_mut`]: struct.SortedMap.html#method.values_mut
+/// [`SortedMap`]: struct.SortedMap.html
+pub struct ValuesMut<'a, K, V> {
+    inner: sorted::IterMut<'a, (K, V)>,
+}
+
+projected_iterator!(['a, K, V] ValuesMut<'a, K, V>, &'a mut V, (_, v) => v);
+
+#[cfg(test)]
+mod tests {
+    use super::SortedMap;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn matches_btree_map() {
+        let mut map = SortedMap::with_load(4);
+        let mut reference = BTreeMap::new();
+        for i in 0..3000u32 {
+            let k = i.wrapping_mul(2_654_435_761) % 1000;
+            if i % 3 == 0 {
+                assert_eq!(map.remove(&k), reference.remove(&k));
+            } else {
+                assert_eq!(map.insert(k, i), reference.insert(k, i));
+            }
+        }
+        assert_eq!(map.len(), reference.len());
+        assert!(map.core.sublists() > 50);
+        assert!(map.iter().eq(reference.iter()));
+        assert!(map.iter().rev().eq(reference.iter().rev()));
+        for (i, (k, v)) in reference.iter().enumerate() {
+            assert_eq!(map.index(k), Some(i));
+            assert_eq!(map.peekitem(i), Some((k, v)));
+        }
+        let range: Vec<_> = map.irange(Some(&100), Some(&200), (true, false)).collect();
+        let expected: Vec<_> = reference.range(100..200).collect();
+        assert_eq!(range, expected);
+    }
+
+    #[test]
+    fn positional_removal() {
+        let mut map: SortedMap<_, _> = vec![(2, 'b'), (1, 'a'), (3, 'c'), (2, 'B')]
+            .into_iter()
+            .collect();
+        assert_eq!(map[&2], 'B');
+        map[&3] = 'C';
+        for v in map.values_mut() {
+            *v = v.to_ascii_lowercase();
+        }
+        assert_eq!(format!("{:?}", map), "{1: 'a', 2: 'b', 3: 'c'}");
+        assert_eq!(map.popitem(1), Some((2, 'b')));
+        assert_eq!(map.first(), Some((&1, &'a')));
+        assert_eq!(map.last(), Some((&3, &'c')));
+        assert_eq!(map.index(&2), None);
+        map.clear();
+        assert_eq!(map.last(), None);
+    }
+}
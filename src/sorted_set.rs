@@ -0,0 +1,582 @@
+use crate::sorted::{self, projected_iterator, SortedCore};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitXor, Bound, Index, RangeBounds, Sub};
+
+/// A set that keeps its items sorted, like Python's `sortedcontainers.SortedSet`, with
+/// rank and select in O(log n).
+///
+/// [`rank`] tells how many items are smaller than a value and [`select`] returns the item at
+/// a position, which `BTreeSet` cannot do without walking the set. The set operations merge
+/// both sets in one linear pass.
+///
+/// [`rank`]: #method.rank
+/// [`select`]: #method.select
+///
+/// # Examples
+///
+/// ```
+/// use ordered::SortedSet;
+///
+/// let mut scores = SortedSet::new();
+/// for score in [420, 1337, 99, 2048].iter() {
+///     scores.insert(*score);
+/// }
+///
+/// assert_eq!(scores.rank(&1337), 2);
+/// assert_eq!(scores.select(0), Some(&99));
+/// let top: Vec<_> = scores.range(1000..).collect();
+/// assert_eq!(top, [&1337, &2048]);
+/// ```
+#[derive(Clone)]
+pub struct SortedSet<T> {
+    core: SortedCore<T>,
+}
+
+impl<T> SortedSet<T> {
+    /// Creates an empty `SortedSet`.
+    #[inline]
+    pub fn new() -> SortedSet<T> {
+        SortedSet {
+            core: SortedCore::new(),
+        }
+    }
+
+    /// Creates an empty `SortedSet` whose sublists aim for `load` items.
+    #[cfg(test)]
+    pub(crate) fn with_load(load: usize) -> SortedSet<T> {
+        SortedSet {
+            core: SortedCore::with_load(load),
+        }
+    }
+
+    /// Returns the number of items in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Returns `true` if the set contains no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.core.len() == 0
+    }
+
+    /// Removes all items from the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Returns the item at position `index` in the sorted order, the inverse of [`rank`].
+    ///
+    /// This is O(log n).
+    ///
+    /// [`rank`]: #method.rank
+    #[inline]
+    pub fn select(&self, index: usize) -> Option<&T> {
+        self.core.get(index)
+    }
+
+    /// Returns the smallest item.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.core.get(0)
+    }
+
+    /// Returns the largest item.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.core.get(self.len().wrapping_sub(1))
+    }
+
+    /// Removes and returns the smallest item.
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.core.remove(0))
+    }
+
+    /// Removes and returns the largest item.
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.core.remove(self.len() - 1))
+    }
+
+    /// Returns the items at the positions `start..stop`, like Python's
+    /// `SortedSet.islice(start, stop)`.
+    ///
+    /// `stop` is clamped to the length of the set, and an empty range yields nothing.
+    #[inline]
+    pub fn islice(&self, start: usize, stop: usize) -> Iter<'_, T> {
+        let stop = usize::min(stop, self.len());
+        let start = usize::min(start, stop);
+        Iter {
+            inner: self.core.range(start, stop),
+        }
+    }
+
+    /// An iterator visiting all items in order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.core.iter(),
+        }
+    }
+
+    /// Builds a set from distinct items that are already in order.
+    #[inline]
+    fn from_sorted<I: Iterator<Item = T>>(iter: I) -> SortedSet<T> {
+        SortedSet {
+            core: SortedCore::from_sorted(iter.collect()),
+        }
+    }
+}
+
+impl<T: Ord> SortedSet<T> {
+    /// Adds a value to the set. Returns whether the value was newly inserted.
+    ///
+    /// This is O(log n).
+    #[inline]
+    pub fn insert(&mut self, value: T) -> bool {
+        let index = self.rank(&value);
+        if self.core.get(index) == Some(&value) {
+            return false;
+        }
+        self.core.insert(index, value);
+        true
+    }
+
+    /// Returns the number of items smaller than `value`, which is the position of `value`
+    /// if it is in the set.
+    ///
+    /// This is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedSet;
+    ///
+    /// let set: SortedSet<_> = vec![10, 20, 30].into_iter().collect();
+    /// assert_eq!(set.rank(&20), 1);
+    /// assert_eq!(set.rank(&25), 2);
+    /// assert_eq!(set.select(set.rank(&30)), Some(&30));
+    /// ```
+    #[inline]
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.core.partition_point(|item| item.borrow() < value)
+    }
+
+    /// Returns the position of `value` in the sorted order, or `None` if it is not in the
+    /// set.
+    #[inline]
+    pub fn index<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.rank(value);
+        match self.core.get(index) {
+            Some(item) if item.borrow() == value => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the set contains a value.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.index(value).is_some()
+    }
+
+    /// Returns a reference to the value in the set, if any, that is equal to the given value.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.index(value).and_then(|index| self.core.get(index))
+    }
+
+    /// Removes a value from the set. Returns whether the value was present.
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.take(value).is_some()
+    }
+
+    /// Removes and returns the value in the set, if any, that is equal to the given one.
+    #[inline]
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.index(value)?;
+        Some(self.core.remove(index))
+    }
+
+    /// An iterator over the items within `range`, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedSet;
+    ///
+    /// let set: SortedSet<_> = (1..=10).collect();
+    /// assert!(set.range(3..6).eq([3, 4, 5].iter()));
+    /// assert!(set.range(..=2).eq([1, 2].iter()));
+    /// assert_eq!(set.range(20..).len(), 0);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(min) => self.rank(min),
+            Bound::Excluded(min) => self.core.partition_point(|item| item.borrow() <= min),
+            Bound::Unbounded => 0,
+        };
+        let stop = match range.end_bound() {
+            Bound::Included(max) => self.core.partition_point(|item| item.borrow() <= max),
+            Bound::Excluded(max) => self.rank(max),
+            Bound::Unbounded => self.len(),
+        };
+        self.islice(start, stop)
+    }
+
+    /// Returns `true` if `self` has no items in common with `other`.
+    #[inline]
+    pub fn is_disjoint(&self, other: &SortedSet<T>) -> bool {
+        merge(self, other, |ordering| ordering == Ordering::Equal)
+            .next()
+            .is_none()
+    }
+
+    /// Returns `true` if every item of `self` is in `other`.
+    #[inline]
+    pub fn is_subset(&self, other: &SortedSet<T>) -> bool {
+        self.len() <= other.len()
+            && merge(self, other, |ordering| ordering == Ordering::Less)
+                .next()
+                .is_none()
+    }
+
+    /// Returns `true` if every item of `other` is in `self`.
+    #[inline]
+    pub fn is_superset(&self, other: &SortedSet<T>) -> bool {
+        other.is_subset(self)
+    }
+}
+
+impl<T: Ord + Clone> SortedSet<T> {
+    /// Returns the items in `self` or `other`, merged in O(n + m).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedSet;
+    ///
+    /// let a: SortedSet<_> = vec![1, 3, 5].into_iter().collect();
+    /// let b: SortedSet<_> = vec![2, 3, 4].into_iter().collect();
+    /// assert!(a.union(&b).iter().eq([1, 2, 3, 4, 5].iter()));
+    /// ```
+    #[inline]
+    pub fn union(&self, other: &SortedSet<T>) -> SortedSet<T> {
+        SortedSet::from_sorted(merge(self, other, |_| true).cloned())
+    }
+
+    /// Returns the items in both `self` and `other`, merged in O(n + m).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedSet;
+    ///
+    /// let a: SortedSet<_> = vec![1, 3, 5].into_iter().collect();
+    /// let b: SortedSet<_> = vec![2, 3, 5].into_iter().collect();
+    /// assert!(a.intersection(&b).iter().eq([3, 5].iter()));
+    /// ```
+    #[inline]
+    pub fn intersection(&self, other: &SortedSet<T>) -> SortedSet<T> {
+        SortedSet::from_sorted(merge(self, other, |ordering| ordering == Ordering::Equal).cloned())
+    }
+
+    /// Returns the items in `self` but not in `other`, merged in O(n + m).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::SortedSet;
+    ///
+    /// let a: SortedSet<_> = vec![1, 3, 5].into_iter().collect();
+    /// let b: SortedSet<_> = vec![2, 3, 4].into_iter().collect();
+    /// assert!(a.difference(&b).iter().eq([1, 5].iter()));
+    /// ```
+    #[inline]
+    pub fn difference(&self, other: &SortedSet<T>) -> SortedSet<T> {
+        SortedSet::from_sorted(merge(self, other, |ordering| ordering == Ordering::Less).cloned())
+    }
+
+    /// Returns the items in `self` or `other` but not in both, merged in O(n + m).
+    #[inline]
+    pub fn symmetric_difference(&self, other: &SortedSet<T>) -> SortedSet<T> {
+        SortedSet::from_sorted(merge(self, other, |ordering| ordering != Ordering::Equal).cloned())
+    }
+}
+
+/// Walks two sorted sets together and yields the items for which `keep` accepts
+/// the comparison between the sides: `Less` for an item only in `a`, `Greater` for an item
+/// only in `b` and `Equal` for an item in both.
+fn merge<'a, T, F>(
+    a: &'a SortedSet<T>,
+    b: &'a SortedSet<T>,
+    mut keep: F,
+) -> impl Iterator<Item = &'a T> + 'a
+where
+    T: Ord,
+    F: FnMut(Ordering) -> bool + 'a,
+{
+    let mut a = a.iter().peekable();
+    let mut b = b.iter().peekable();
+    std::iter::from_fn(move || loop {
+        let (item, ordering) = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => match x.cmp(y) {
+                Ordering::Less => (a.next()?, Ordering::Less),
+                Ordering::Greater => (b.next()?, Ordering::Greater),
+                Ordering::Equal => {
+                    b.next();
+                    (a.next()?, Ordering::Equal)
+                }
+            },
+            (Some(_), None) => (a.next()?, Ordering::Less),
+            (None, Some(_)) => (b.next()?, Ordering::Greater),
+            (None, None) => return None,
+        };
+        if keep(ordering) {
+            return Some(item);
+        }
+    })
+}
+
+impl<T> Default for SortedSet<T> {
+    /// Creates an empty `SortedSet<T>`.
+    #[inline]
+    fn default() -> SortedSet<T> {
+        SortedSet::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SortedSet<T> {
+    #[inline]
+    fn eq(&self, other: &SortedSet<T>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for SortedSet<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for SortedSet<T> {
+    /// Formats the set as `{a, b, ...}` in order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<usize> for SortedSet<T> {
+    type Output = T;
+
+    /// Returns the item at position `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.select(index).expect("SortedSet: index out of bounds")
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedSet<T> {
+    /// Builds the set in O(n log n), keeping the first of equal items.
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SortedSet<T> {
+        let mut items: Vec<_> = iter.into_iter().collect();
+        items.sort();
+        items.dedup();
+        SortedSet {
+            core: SortedCore::from_sorted(items),
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for SortedSet<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T: Ord + Clone> BitOr<&SortedSet<T>> for &SortedSet<T> {
+    type Output = SortedSet<T>;
+
+    /// Returns the union of `self` and `rhs` as a new `SortedSet<T>`.
+    #[inline]
+    fn bitor(self, rhs: &SortedSet<T>) -> SortedSet<T> {
+        self.union(rhs)
+    }
+}
+
+impl<T: Ord + Clone> BitAnd<&SortedSet<T>> for &SortedSet<T> {
+    type Output = SortedSet<T>;
+
+    /// Returns the intersection of `self` and `rhs` as a new `SortedSet<T>`.
+    #[inline]
+    fn bitand(self, rhs: &SortedSet<T>) -> SortedSet<T> {
+        self.intersection(rhs)
+    }
+}
+
+impl<T: Ord + Clone> Sub<&SortedSet<T>> for &SortedSet<T> {
+    type Output = SortedSet<T>;
+
+    /// Returns the difference of `self` and `rhs` as a new `SortedSet<T>`.
+    #[inline]
+    fn sub(self, rhs: &SortedSet<T>) -> SortedSet<T> {
+        self.difference(rhs)
+    }
+}
+
+impl<T: Ord + Clone> BitXor<&SortedSet<T>> for &SortedSet<T> {
+    type Output = SortedSet<T>;
+
+    /// Returns the symmetric difference of `self` and `rhs` as a new `SortedSet<T>`.
+    #[inline]
+    fn bitxor(self, rhs: &SortedSet<T>) -> SortedSet<T> {
+        self.symmetric_difference(rhs)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SortedSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for SortedSet<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            inner: self.core.into_iter(),
+        }
+    }
+}
+
+/// An iterator over the items of a `SortedSet` in order.
+///
+/// This `struct` is created by the [`iter`], [`islice`] and [`range`] methods on
+/// [`SortedSet`].
+///
+/// [`iter`]: struct.SortedSet.html#method.iter
+/// [`islice`]: struct.SortedSet.html#method.islice
+/// [`range`]: struct.SortedSet.html#method.range
+/// [`SortedSet`]: struct.SortedSet.html
+pub struct Iter<'a, T> {
+    inner: sorted::Iter<'a, T>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+projected_iterator!(['a, T] Iter<'a, T>, &'a T, item => item);
+
+/// An owning iterator over the items of a `SortedSet` in order.
+///
+/// This `struct` is created by the [`into_iter`] method on [`SortedSet`]
+/// (provided by the `IntoIterator` trait).
+///
+/// [`into_iter`]: struct.SortedSet.html#method.into_iter
+/// [`SortedSet`]: struct.SortedSet.html
+pub struct IntoIter<T> {
+    inner: sorted::IntoIter<T>,
+}
+
+projected_iterator!([T] IntoIter<T>, T, item => item);
+
+#[cfg(test)]
+mod tests {
+    use super::SortedSet;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn rank_and_select_match_btree_set() {
+        let mut set = SortedSet::with_load(4);
+        let mut reference = BTreeSet::new();
+        for i in 0..3000u32 {
+            let value = i.wrapping_mul(2_654_435_761) % 500;
+            if i % 3 == 0 {
+                assert_eq!(set.remove(&value), reference.remove(&value));
+            } else {
+                assert_eq!(set.insert(value), reference.insert(value));
+            }
+        }
+        assert!(set.core.sublists() > 30);
+        assert!(set.iter().eq(reference.iter()));
+        for (i, value) in reference.iter().enumerate() {
+            assert_eq!(set.rank(value), i);
+            assert_eq!(set[i], *value);
+        }
+        assert!(set.range(100..=200).eq(reference.range(100..=200)));
+        assert_eq!(set.pop_first(), reference.iter().next().cloned());
+        assert_eq!(set.pop_last(), reference.iter().next_back().cloned());
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a: SortedSet<_> = (0..20).filter(|i| i % 2 == 0).collect();
+        let b: SortedSet<_> = (0..20).filter(|i| i % 3 == 0).collect();
+        let c = &a | &b;
+        assert!((&a & &b).iter().eq([0, 6, 12, 18].iter()));
+        assert!((&b - &a).iter().eq([3, 9, 15].iter()));
+        assert_eq!((&a ^ &b).len(), c.len() - 4);
+        assert!(a.is_subset(&c) && c.is_superset(&b));
+        assert!(!a.is_subset(&b));
+        assert!((&a - &b).is_disjoint(&b));
+        assert_eq!(format!("{:?}", &a & &b), "{0, 6, 12, 18}");
+    }
+}
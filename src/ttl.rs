@@ -0,0 +1,465 @@
+use crate::map::OrderedHashMap;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, Instant};
+
+/// A source of the current time for a [`TtlCache`].
+///
+/// Tests can implement it on a fake clock that they advance by hand instead of sleeping.
+///
+/// [`TtlCache`]: struct.TtlCache.html
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// The [`Clock`] that reads `Instant::now()`.
+///
+/// [`Clock`]: trait.Clock.html
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A cache whose entries expire a fixed time after they were inserted.
+///
+/// Entries are kept in an `OrderedHashMap` in expiry order. With the cache's default TTL
+/// that is simply insertion order, so inserting is O(1) and [`purge_expired`] only looks at
+/// the front of the map. An entry with its own TTL that expires before entries already cached
+/// is inserted in the middle of the map, which is O(n).
+///
+/// A TTL that reaches past the latest representable `Instant` means the entry never expires.
+///
+/// Expired entries are removed lazily: a lookup of an expired key removes it and misses, and
+/// every insertion purges the expired entries at the front. Until then they still count
+/// towards [`len`].
+///
+/// [`purge_expired`]: #method.purge_expired
+/// [`len`]: #method.len
+///
+/// # Examples
+///
+/// ```
+/// use ordered::TtlCache;
+/// use std::time::Duration;
+///
+/// let mut sessions = TtlCache::new(Duration::from_secs(60));
+/// sessions.insert("alice", 1);
+/// assert_eq!(sessions.get(&"alice"), Some(&1));
+/// assert_eq!(sessions.get(&"bob"), None);
+/// ```
+#[derive(Clone)]
+pub struct TtlCache<K, V, C = SystemClock, S = RandomState> {
+    map: OrderedHashMap<K, (Option<Instant>, V), S>,
+    ttl: Duration,
+    clock: C,
+}
+
+impl<K, V> TtlCache<K, V, SystemClock, RandomState> {
+    /// Creates an empty `TtlCache` whose entries expire `ttl` after insertion by default.
+    #[inline]
+    pub fn new(ttl: Duration) -> TtlCache<K, V, SystemClock, RandomState> {
+        TtlCache::with_clock(ttl, SystemClock)
+    }
+}
+
+impl<K, V, C: Clock> TtlCache<K, V, C, RandomState> {
+    /// Creates an empty `TtlCache` that reads the time from `clock`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::{Clock, TtlCache};
+    /// use std::cell::Cell;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// struct FakeClock(Cell<Instant>);
+    ///
+    /// impl Clock for &FakeClock {
+    ///     fn now(&self) -> Instant {
+    ///         self.0.get()
+    ///     }
+    /// }
+    ///
+    /// let clock = FakeClock(Cell::new(Instant::now()));
+    /// let mut cache = TtlCache::with_clock(Duration::from_secs(10), &clock);
+    /// cache.insert("a", 1);
+    ///
+    /// clock.0.set(clock.0.get() + Duration::from_secs(10));
+    /// assert_eq!(cache.get(&"a"), None);
+    /// assert!(cache.is_empty());
+    /// ```
+    #[inline]
+    pub fn with_clock(ttl: Duration, clock: C) -> TtlCache<K, V, C, RandomState> {
+        TtlCache::with_clock_and_hasher(ttl, clock, RandomState::new())
+    }
+}
+
+impl<K, V, C: Clock, S> TtlCache<K, V, C, S> {
+    /// Creates an empty `TtlCache` that reads the time from `clock` and uses the given hash
+    /// builder to hash keys.
+    #[inline]
+    pub fn with_clock_and_hasher(ttl: Duration, clock: C, hash_builder: S) -> TtlCache<K, V, C, S> {
+        TtlCache {
+            map: OrderedHashMap::with_hasher(hash_builder).unranked(),
+            ttl,
+            clock,
+        }
+    }
+
+    /// Returns the default time to live of the entries.
+    #[inline]
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the clock of the cache.
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns the number of entries in the cache, including expired entries that have not
+    /// been purged yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the cache contains no entries, expired or not.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all entries from the cache.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Removes every entry that has expired at `now` and returns how many were removed.
+    ///
+    /// Entries are stored in expiry order, so this pops entries off the front until the first
+    /// live one, which is O(1) per removed entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::TtlCache;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// cache.insert(1, "a");
+    /// cache.insert_with_ttl(2, "b", Duration::from_secs(1));
+    ///
+    /// let later = Instant::now() + Duration::from_secs(2);
+    /// assert_eq!(cache.purge_expired(later), 1);
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn purge_expired(&mut self, now: Instant) -> usize {
+        let mut expired = 0;
+        while let Some((_, &(expires_at, _))) = self.map.front() {
+            if !is_expired(expires_at, now) {
+                break;
+            }
+            self.map.pop_front();
+            expired += 1;
+        }
+        expired
+    }
+
+    /// An iterator over the live entries in expiry order, soonest first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::TtlCache;
+    /// use std::time::Duration;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(60));
+    /// cache.insert("a", 1);
+    /// cache.insert_with_ttl("b", 2, Duration::from_secs(30));
+    /// let keys: Vec<_> = cache.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, ["b", "a"]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let now = self.clock.now();
+        self.map
+            .iter()
+            .skip_while(move |&(_, &(expires_at, _))| is_expired(expires_at, now))
+            .map(|(k, (_, v))| (k, v))
+    }
+}
+
+impl<K, V, C, S> TtlCache<K, V, C, S>
+where
+    K: Eq + Hash,
+    C: Clock,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair that expires after the cache's default TTL, returning the old
+    /// value if the key was cached and had not expired.
+    ///
+    /// This also purges the expired entries at the front of the cache.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.insert_with_ttl(k, v, self.ttl)
+    }
+
+    /// Inserts a key-value pair that expires after `ttl`, returning the old value if the key
+    /// was cached and had not expired.
+    ///
+    /// This also purges the expired entries at the front of the cache. If the entry expires
+    /// before entries already cached, it is inserted among them, which is O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::TtlCache;
+    /// use std::time::Duration;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(60));
+    /// assert_eq!(cache.insert_with_ttl("a", 1, Duration::from_secs(1)), None);
+    /// assert_eq!(cache.insert("a", 2), Some(1));
+    /// ```
+    pub fn insert_with_ttl(&mut self, k: K, v: V, ttl: Duration) -> Option<V> {
+        let now = self.clock.now();
+        let old = self.remove_live(&k, now);
+        self.purge_expired(now);
+
+        let expires_at = now.checked_add(ttl);
+        let later = self
+            .map
+            .values()
+            .rev()
+            .take_while(|&&(other, _)| match (other, expires_at) {
+                (Some(other), Some(expires_at)) => other > expires_at,
+                (None, Some(_)) => true,
+                (_, None) => false,
+            })
+            .count();
+        if later == 0 {
+            self.map.insert(k, (expires_at, v));
+        } else {
+            let index = self.map.len() - later;
+            self.map.insert_at(index, k, (expires_at, v));
+        }
+        old
+    }
+
+    /// Returns a reference to the value of the key if it is cached and has not expired.
+    ///
+    /// An expired entry is removed.
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.get_mut(k).map(|v| &*v)
+    }
+
+    /// Returns a mutable reference to the value of the key if it is cached and has not
+    /// expired.
+    ///
+    /// An expired entry is removed.
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let now = self.clock.now();
+        if is_expired(self.map.get(k)?.0, now) {
+            self.map.remove(k);
+            return None;
+        }
+        self.map.get_mut(k).map(|(_, v)| v)
+    }
+
+    /// Returns `true` if the key is cached and has not expired.
+    ///
+    /// An expired entry is removed.
+    #[inline]
+    pub fn contains_key<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.get_mut(k).is_some()
+    }
+
+    /// Returns the time at which the key expires, if it is cached and has not expired.
+    ///
+    /// An entry that never expires has no such time and also returns `None`.
+    pub fn expires_at<Q>(&self, k: &Q) -> Option<Instant>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let &(expires_at, _) = self.map.get(k)?;
+        if is_expired(expires_at, self.clock.now()) {
+            return None;
+        }
+        expires_at
+    }
+
+    /// Removes a key from the cache, returning its value if it was cached and had not
+    /// expired.
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let now = self.clock.now();
+        self.remove_live(k, now)
+    }
+
+    /// Removes a key and returns its value unless it had expired at `now`.
+    fn remove_live<Q>(&mut self, k: &Q, now: Instant) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        match self.map.remove(k)? {
+            (expires_at, _) if is_expired(expires_at, now) => None,
+            (_, v) => Some(v),
+        }
+    }
+}
+
+/// Returns `true` if an entry expiring at `expires_at` has expired at `now`. `None` never
+/// expires.
+#[inline]
+fn is_expired(expires_at: Option<Instant>, now: Instant) -> bool {
+    match expires_at {
+        Some(expires_at) => expires_at <= now,
+        None => false,
+    }
+}
+
+impl<K, V, C, S> fmt::Debug for TtlCache<K, V, C, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+    C: Clock,
+{
+    /// Formats the live entries as `{k: v, ...}` in expiry order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, TtlCache};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    #[derive(Clone)]
+    struct FakeClock(Rc<Cell<Instant>>);
+
+    impl FakeClock {
+        fn advance(&self, secs: u64) {
+            self.0.set(self.0.get() + Duration::from_secs(secs));
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    fn keys(cache: &TtlCache<u32, u32, FakeClock>) -> Vec<u32> {
+        cache.iter().map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn entries_expire_in_order() {
+        let clock = FakeClock(Rc::new(Cell::new(Instant::now())));
+        let mut cache = TtlCache::with_clock(Duration::from_secs(10), clock.clone());
+        cache.insert(1, 10);
+        clock.advance(3);
+        cache.insert(2, 20);
+        cache.insert_with_ttl(3, 30, Duration::from_secs(1));
+        cache.insert_with_ttl(4, 40, Duration::from_secs(100));
+        cache.insert_with_ttl(5, 50, Duration::from_secs(5));
+        assert_eq!(keys(&cache), [3, 5, 1, 2, 4]);
+
+        clock.advance(1);
+        assert_eq!(cache.len(), 5);
+        assert_eq!(cache.get(&3), None);
+        assert_eq!(cache.len(), 4);
+
+        clock.advance(6);
+        assert_eq!(keys(&cache), [2, 4]);
+        assert_eq!(cache.purge_expired(clock.now()), 2);
+        assert_eq!(cache.get(&2), Some(&20));
+        assert_eq!(format!("{:?}", cache), "{2: 20, 4: 40}");
+
+        clock.advance(3);
+        assert_eq!(cache.insert(1, 11), None);
+        assert_eq!(keys(&cache), [1, 4]);
+        assert_eq!(cache.remove(&4), Some(40));
+    }
+
+    #[test]
+    fn expired_values_are_not_returned() {
+        let clock = FakeClock(Rc::new(Cell::new(Instant::now())));
+        let mut cache = TtlCache::with_clock(Duration::from_secs(2), clock.clone());
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        *cache.get_mut(&"a").unwrap() += 10;
+        assert_eq!(cache.insert("a", 3), Some(11));
+
+        clock.advance(2);
+        assert_eq!(cache.expires_at(&"a"), None);
+        assert_eq!(cache.insert("a", 4), None);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains_key(&"a"));
+        assert!(!cache.contains_key(&"b"));
+    }
+
+    #[test]
+    fn insert_is_constant_time_in_the_cache_size() {
+        let clock = FakeClock(Rc::new(Cell::new(Instant::now())));
+        let mut cache = TtlCache::with_clock(Duration::from_secs(20_000), clock.clone());
+        for i in 0..100_000 {
+            cache.insert(i, i);
+            clock.advance(1);
+        }
+        assert_eq!(cache.len(), 20_000);
+        assert_eq!(cache.iter().next(), Some((&80_001, &80_001)));
+    }
+
+    #[test]
+    fn unrepresentable_expiry_never_expires() {
+        let clock = FakeClock(Rc::new(Cell::new(Instant::now())));
+        let mut cache = TtlCache::with_clock(Duration::MAX, clock.clone());
+        cache.insert(1, 10);
+        cache.insert_with_ttl(2, 20, Duration::from_secs(5));
+        cache.insert_with_ttl(3, 30, Duration::MAX);
+        cache.insert_with_ttl(4, 40, Duration::from_secs(1));
+        assert_eq!(keys(&cache), [4, 2, 1, 3]);
+        assert_eq!(cache.expires_at(&1), None);
+        assert_eq!(
+            cache.expires_at(&4),
+            Some(clock.now() + Duration::from_secs(1))
+        );
+
+        clock.advance(1_000_000);
+        assert_eq!(cache.purge_expired(clock.now()), 2);
+        assert_eq!(cache.insert(1, 11), Some(10));
+        assert_eq!(keys(&cache), [3, 1]);
+    }
+}
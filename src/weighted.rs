@@ -0,0 +1,474 @@
+use crate::lru::Displaced;
+use crate::map::{self, OrderedHashMap};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FusedIterator;
+use std::vec;
+
+/// Measures how much of a [`WeightedCache`]'s limit an entry uses, for example the size of
+/// a value in bytes.
+///
+/// The weight of an entry must not change while it is cached. Closures taking `(&K, &V)`
+/// implement this trait.
+///
+/// [`WeightedCache`]: struct.WeightedCache.html
+pub trait Weigher<K, V> {
+    /// Returns the weight of an entry.
+    fn weight(&self, key: &K, value: &V) -> usize;
+}
+
+impl<K, V, F> Weigher<K, V> for F
+where
+    F: Fn(&K, &V) -> usize,
+{
+    #[inline]
+    fn weight(&self, key: &K, value: &V) -> usize {
+        self(key, value)
+    }
+}
+
+/// A least-recently-used cache bounded by the total weight of its entries rather than by
+/// their number.
+///
+/// Entries are kept in an `OrderedHashMap` from least to most recently used. Each [`put`]
+/// pops entries from the front until the new entry fits within the limit, so evicting costs
+/// O(1) per evicted entry. An entry heavier than the whole limit is never cached: [`put`]
+/// hands it straight back and leaves the cache untouched.
+///
+/// [`put`]: #method.put
+///
+/// # Examples
+///
+/// ```
+/// use ordered::{Displaced, WeightedCache};
+///
+/// let mut blobs = WeightedCache::new(10, |_: &&str, blob: &Vec<u8>| blob.len());
+/// assert_eq!(blobs.put("a", vec![0; 4]).count(), 0);
+/// assert_eq!(blobs.put("b", vec![0; 4]).count(), 0);
+/// assert_eq!(blobs.weight(), 8);
+///
+/// let displaced: Vec<_> = blobs.put("c", vec![0; 3]).collect();
+/// assert_eq!(displaced, [Displaced::Evicted("a", vec![0; 4])]);
+/// assert_eq!(blobs.weight(), 7);
+/// ```
+#[derive(Clone)]
+pub struct WeightedCache<K, V, W, S = RandomState> {
+    map: OrderedHashMap<K, V, S>,
+    weigher: W,
+    limit: usize,
+    weight: usize,
+}
+
+impl<K, V, W> WeightedCache<K, V, W, RandomState>
+where
+    W: Weigher<K, V>,
+{
+    /// Creates an empty `WeightedCache` whose entries may weigh at most `limit` in total.
+    #[inline]
+    pub fn new(limit: usize, weigher: W) -> WeightedCache<K, V, W, RandomState> {
+        WeightedCache::with_hasher(limit, weigher, RandomState::new())
+    }
+}
+
+impl<K, V, W, S> WeightedCache<K, V, W, S>
+where
+    W: Weigher<K, V>,
+{
+    /// Creates an empty `WeightedCache` whose entries may weigh at most `limit` in total,
+    /// using the given hash builder to hash keys.
+    #[inline]
+    pub fn with_hasher(limit: usize, weigher: W, hash_builder: S) -> WeightedCache<K, V, W, S> {
+        WeightedCache {
+            map: OrderedHashMap::with_hasher(hash_builder).unranked(),
+            weigher,
+            limit,
+            weight: 0,
+        }
+    }
+
+    /// Returns the maximum total weight of the cached entries.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the current total weight of the cached entries.
+    #[inline]
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Returns the weigher of the cache.
+    #[inline]
+    pub fn weigher(&self) -> &W {
+        &self.weigher
+    }
+
+    /// Returns the number of entries in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the cache contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all entries from the cache.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.weight = 0;
+    }
+
+    /// Returns the least recently used entry without promoting it.
+    #[inline]
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.map.front()
+    }
+
+    /// Removes and returns the least recently used entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (k, v) = self.map.pop_front()?;
+        self.weight -= self.weigher.weight(&k, &v);
+        Some((k, v))
+    }
+
+    /// Changes the weight limit of the cache, evicting the least recently used entries until
+    /// the total weight fits.
+    ///
+    /// The evicted entries are returned from least to most recently used. They are removed
+    /// even if the iterator is dropped early.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::WeightedCache;
+    ///
+    /// let mut cache = WeightedCache::new(10, |_: &char, w: &usize| *w);
+    /// cache.extend(vec![('a', 3), ('b', 3), ('c', 3)]);
+    /// let evicted: Vec<_> = cache.set_limit(5).collect();
+    /// assert_eq!(evicted, [('a', 3), ('b', 3)]);
+    /// assert_eq!(cache.weight(), 3);
+    /// ```
+    #[inline]
+    pub fn set_limit(&mut self, limit: usize) -> map::Drain<'_, K, V> {
+        self.limit = limit;
+        let count = self.excess(limit);
+        self.map.drain(..count)
+    }
+
+    /// An iterator over the entries from least to most recently used, without promoting
+    /// them.
+    #[inline]
+    pub fn iter(&self) -> map::Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    /// Unwraps the underlying map, ordered from least to most recently used.
+    #[inline]
+    pub fn into_map(self) -> OrderedHashMap<K, V, S> {
+        self.map.ranked()
+    }
+
+    /// Counts the entries at the front that must go for the total weight to be at most
+    /// `budget`, and takes their weight off the total.
+    fn excess(&mut self, budget: usize) -> usize {
+        let mut count = 0;
+        for (k, v) in self.map.iter() {
+            if self.weight <= budget {
+                break;
+            }
+            self.weight -= self.weigher.weight(k, v);
+            count += 1;
+        }
+        count
+    }
+}
+
+impl<K, V, W, S> WeightedCache<K, V, W, S>
+where
+    K: Eq + Hash,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair as the most recently used entry, evicting the least recently
+    /// used entries until it fits within the limit.
+    ///
+    /// Returns what was pushed out: the old value as [`Displaced::Replaced`] if the key was
+    /// already cached, then each evicted entry as [`Displaced::Evicted`], from least to most
+    /// recently used.
+    ///
+    /// If the new entry weighs more than the limit on its own, it is not cached and is
+    /// returned as the only [`Displaced::Evicted`] entry. The cache, including any value
+    /// already cached for the key, is left untouched.
+    ///
+    /// [`Displaced::Replaced`]: enum.Displaced.html#variant.Replaced
+    /// [`Displaced::Evicted`]: enum.Displaced.html#variant.Evicted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ordered::{Displaced, WeightedCache};
+    ///
+    /// let mut cache = WeightedCache::new(4, |_: &u8, s: &&str| s.len());
+    /// assert_eq!(cache.put(1, "ab").count(), 0);
+    /// let displaced: Vec<_> = cache.put(1, "abc").collect();
+    /// assert_eq!(displaced, [Displaced::Replaced("ab")]);
+    ///
+    /// let displaced: Vec<_> = cache.put(1, "abcde").collect();
+    /// assert_eq!(displaced, [Displaced::Evicted(1, "abcde")]);
+    /// assert_eq!(cache.peek(&1), Some(&"abc"));
+    ///
+    /// let displaced: Vec<_> = cache.put(3, "ab").collect();
+    /// assert_eq!(displaced, [Displaced::Evicted(1, "abc")]);
+    /// assert_eq!(cache.weight(), 2);
+    /// ```
+    pub fn put(&mut self, k: K, v: V) -> Displacements<K, V> {
+        let mut displaced = Vec::new();
+        let weight = self.weigher.weight(&k, &v);
+        if weight > self.limit {
+            displaced.push(Displaced::Evicted(k, v));
+        } else {
+            if let Some(old) = self.pop(&k) {
+                displaced.push(Displaced::Replaced(old));
+            }
+            while self.weight > self.limit - weight {
+                let (k, v) = self
+                    .pop_lru()
+                    .expect("the weight is carried by cached entries");
+                displaced.push(Displaced::Evicted(k, v));
+            }
+            self.weight += weight;
+            self.map.insert(k, v);
+        }
+        Displacements {
+            inner: displaced.into_iter(),
+        }
+    }
+
+    /// Returns a reference to the value of the key and marks it as the most recently used
+    /// entry.
+    #[inline]
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.move_to_end_mut(k).map(|v| &*v)
+    }
+
+    /// Returns a reference to the value of the key without promoting it.
+    #[inline]
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(k)
+    }
+
+    /// Returns `true` if the key is cached, without promoting it.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.contains_key(k)
+    }
+
+    /// Removes a key from the cache, returning its value if it was cached.
+    pub fn pop<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (k, v) = self.map.shift_remove_entry(k)?;
+        self.weight -= self.weigher.weight(&k, &v);
+        Some(v)
+    }
+}
+
+/// What a [`put`] into a [`WeightedCache`] pushed out: the replaced value first, if any,
+/// then the evicted entries from least to most recently used.
+///
+/// [`put`]: struct.WeightedCache.html#method.put
+/// [`WeightedCache`]: struct.WeightedCache.html
+pub struct Displacements<K, V> {
+    inner: vec::IntoIter<Displaced<K, V>>,
+}
+
+impl<K, V> Iterator for Displacements<K, V> {
+    type Item = Displaced<K, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Displaced<K, V>> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Displacements<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Displaced<K, V>> {
+        self.inner.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Displacements<K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<K, V> FusedIterator for Displacements<K, V> {}
+
+impl<K, V, W, S> Extend<(K, V)> for WeightedCache<K, V, W, S>
+where
+    K: Eq + Hash,
+    W: Weigher<K, V>,
+    S: BuildHasher,
+{
+    /// Puts every pair of `iter` into the cache in order, dropping evicted entries.
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.put(k, v);
+        }
+    }
+}
+
+impl<K, V, W, S> fmt::Debug for WeightedCache<K, V, W, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Formats the cache as `{k: v, ...}` from least to most recently used, leaving out the
+    /// weigher.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<'a, K, V, W, S> IntoIterator for &'a WeightedCache<K, V, W, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = map::Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> map::Iter<'a, K, V> {
+        self.map.iter()
+    }
+}
+
+impl<K, V, W, S> IntoIterator for WeightedCache<K, V, W, S> {
+    type Item = (K, V);
+    type IntoIter = map::IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> map::IntoIter<K, V> {
+        self.map.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Weigher, WeightedCache};
+    use crate::lru::Displaced;
+
+    struct ByValue;
+
+    impl Weigher<u32, usize> for ByValue {
+        fn weight(&self, _: &u32, value: &usize) -> usize {
+            *value
+        }
+    }
+
+    fn keys(cache: &WeightedCache<u32, usize, ByValue>) -> Vec<u32> {
+        cache.iter().map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn evicts_by_weight_in_recency_order() {
+        let mut cache = WeightedCache::new(10, ByValue);
+        cache.extend(vec![(1, 2), (2, 3), (3, 4)]);
+        assert_eq!(cache.weight(), 9);
+        assert_eq!(cache.get(&1), Some(&2));
+
+        let displaced: Vec<_> = cache.put(4, 5).collect();
+        assert_eq!(
+            displaced,
+            [Displaced::Evicted(2, 3), Displaced::Evicted(3, 4)]
+        );
+        assert_eq!(cache.weight(), 7);
+
+        let displaced: Vec<_> = cache.put(1, 1).collect();
+        assert_eq!(displaced, [Displaced::Replaced(2)]);
+        assert_eq!(cache.weight(), 6);
+        assert_eq!(keys(&cache), [4, 1]);
+
+        let displaced: Vec<_> = cache.put(1, 9).collect();
+        assert_eq!(
+            displaced,
+            [Displaced::Replaced(1), Displaced::Evicted(4, 5)]
+        );
+        assert_eq!(cache.weight(), 9);
+
+        assert_eq!(cache.pop_lru(), Some((1, 9)));
+        assert_eq!(cache.weight(), 0);
+    }
+
+    #[test]
+    fn zero_weight_entries_are_kept() {
+        let mut cache = WeightedCache::new(0, ByValue);
+        cache.extend((0..5).map(|k| (k, 0)));
+        assert_eq!(cache.len(), 5);
+        let displaced: Vec<_> = cache.put(5, 1).collect();
+        assert_eq!(displaced, [Displaced::Evicted(5, 1)]);
+        assert_eq!(cache.len(), 5);
+        assert_eq!(cache.weight(), 0);
+    }
+
+    #[test]
+    fn oversized_entry_leaves_the_cache_untouched() {
+        let mut cache = WeightedCache::new(10, ByValue);
+        cache.extend(vec![(1, 3), (2, 3)]);
+        let displaced: Vec<_> = cache.put(3, 100).collect();
+        assert_eq!(displaced, [Displaced::Evicted(3, 100)]);
+        assert_eq!(cache.weight(), 6);
+        assert_eq!(keys(&cache), [1, 2]);
+
+        let displaced: Vec<_> = cache.put(1, 11).collect();
+        assert_eq!(displaced, [Displaced::Evicted(1, 11)]);
+        assert_eq!(cache.peek(&1), Some(&3));
+        assert_eq!(keys(&cache), [1, 2]);
+        assert_eq!(cache.weight(), 6);
+
+        let mut huge = WeightedCache::new(usize::MAX, ByValue);
+        assert_eq!(huge.put(1, usize::MAX - 1).count(), 0);
+        let displaced: Vec<_> = huge.put(2, 2).collect();
+        assert_eq!(displaced, [Displaced::Evicted(1, usize::MAX - 1)]);
+        assert_eq!(huge.weight(), 2);
+    }
+
+    #[test]
+    fn put_is_constant_time_in_the_cache_size() {
+        let mut cache = WeightedCache::new(20_000, ByValue);
+        for i in 0..100_000 {
+            assert!(cache.put(i, 1).len() <= 1);
+        }
+        assert_eq!(cache.len(), 20_000);
+        assert_eq!(cache.set_limit(10_000).len(), 10_000);
+        assert_eq!(cache.set_limit(10_000).len(), 0);
+        assert_eq!(cache.peek_lru(), Some((&90_000, &1)));
+    }
+}